@@ -0,0 +1,154 @@
+// Servis anahtarının Shamir payları ile yedeklenmesi ve geri yüklenmesi.
+//
+//   crypt-admin generate <anahtar-dosyası>
+//   crypt-admin split <anahtar-dosyası> <eşik> <pay-sayısı> [<sahip-anahtarları-dosyası>|-]
+//   crypt-admin restore <anahtar-dosyası> [<paylar-dosyası>|-]
+//   crypt-admin import-legacy <anahtar-dosyası> [<eski-anahtarlar-dosyası>|-]
+//
+// Sahip anahtarları ve paylar komut satırında verilmez (ps ve kabuk geçmişinde
+// görünürler); dosyadan ya da "-" veya dosya verilmezse stdin'den satır satır okunur.
+// Sahip anahtarları verilirse her pay ilgili 32 byte'lık anahtarla (hex) şifrelenir ve
+// geri yüklerken "<sahip-anahtarı-hex>@<şifreli-pay>" biçiminde verilmelidir.
+// import-legacy eski sistemin AES-CTR ve HMAC anahtarlarını (bu sırayla, satır başına
+// 32 byte hex) anahtar dosyasına ekler.
+//
+// Servisler anahtar dosyasını KEY_MATERIAL_FILE ortam değişkeninden okur.
+
+use backend::crypt::CryptService;
+use backend::legacy::LegacyKeys;
+use backend::shamir::Share;
+use std::env;
+use std::fs::{self, OpenOptions, Permissions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::process;
+
+fn usage() -> ! {
+    eprintln!("Kullanım:");
+    eprintln!("  crypt-admin generate <anahtar-dosyası>");
+    eprintln!("  crypt-admin split <anahtar-dosyası> <eşik> <pay-sayısı> [<sahip-anahtarları-dosyası>|-]");
+    eprintln!("  crypt-admin restore <anahtar-dosyası> [<paylar-dosyası>|-]");
+    eprintln!("  crypt-admin import-legacy <anahtar-dosyası> [<eski-anahtarlar-dosyası>|-]");
+    process::exit(2);
+}
+
+fn fail(message: String) -> ! {
+    eprintln!("Hata: {}", message);
+    process::exit(1);
+}
+
+fn load_service(key_file: &str) -> CryptService {
+    let material = fs::read(key_file)
+        .unwrap_or_else(|e| fail(format!("{} okunamadı: {}", key_file, e)));
+    CryptService::from_key_material(&material)
+        .unwrap_or_else(|e| fail(e.to_string()))
+}
+
+// Dosya özel anahtarı düz metin içerir; yalnızca sahibi okuyabilir (0600)
+fn write_service(key_file: &str, service: &CryptService) {
+    let material = service.export_key_material()
+        .unwrap_or_else(|e| fail(e.to_string()));
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(key_file)
+        .unwrap_or_else(|e| fail(format!("{} yazılamadı: {}", key_file, e)));
+    // mode yalnızca yeni dosyada uygulanır; var olan dosyanın izinleri de daraltılır
+    file.set_permissions(Permissions::from_mode(0o600))
+        .and_then(|_| file.write_all(&material))
+        .unwrap_or_else(|e| fail(format!("{} yazılamadı: {}", key_file, e)));
+}
+
+// Boş satırlar atlanır; "-" stdin demektir
+fn read_lines(source: &str) -> Vec<String> {
+    let mut text = String::new();
+    let read = if source == "-" {
+        io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        fs::read_to_string(source).map(|content| text = content)
+    };
+    read.unwrap_or_else(|e| fail(format!("{} okunamadı: {}", source, e)));
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Anahtar hata mesajına yazılmaz
+fn decode_custodian_key(hex_key: &str) -> Vec<u8> {
+    hex::decode(hex_key).unwrap_or_else(|_| fail("Geçersiz sahip anahtarı (hex bekleniyor)".to_string()))
+}
+
+fn decode_legacy_key(hex_key: &str) -> [u8; 32] {
+    hex::decode(hex_key).ok()
+        .and_then(|key| key.try_into().ok())
+        .unwrap_or_else(|| fail("Geçersiz eski anahtar (32 byte hex bekleniyor)".to_string()))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["generate", key_file] => {
+            write_service(key_file, &CryptService::generate());
+            println!("Yeni anahtar materyali oluşturuldu: {}", key_file);
+        }
+        ["split", key_file, threshold, count, rest @ ..] if rest.len() <= 1 => {
+            let threshold: u8 = threshold.parse().unwrap_or_else(|_| usage());
+            let count: u8 = count.parse().unwrap_or_else(|_| usage());
+            let custodian_keys = rest.first().map(|source| read_lines(source)).unwrap_or_default();
+            if !custodian_keys.is_empty() && custodian_keys.len() != count as usize {
+                fail(format!("{} pay için {} sahip anahtarı verildi", count, custodian_keys.len()));
+            }
+
+            let shares = load_service(key_file)
+                .split_key_material(threshold, count)
+                .unwrap_or_else(|e| fail(e.to_string()));
+
+            for (i, share) in shares.iter().enumerate() {
+                let blob = match custodian_keys.get(i) {
+                    Some(hex_key) => share.seal(&decode_custodian_key(hex_key))
+                        .unwrap_or_else(|e| fail(e.to_string())),
+                    None => share.to_printable(),
+                };
+                println!("{}", blob);
+            }
+        }
+        ["restore", key_file, rest @ ..] if rest.len() <= 1 => {
+            let shares = read_lines(rest.first().copied().unwrap_or("-"));
+            if shares.is_empty() {
+                fail("Pay verilmedi".to_string());
+            }
+            let shares: Vec<Share> = shares.iter()
+                .map(|arg| match arg.split_once('@') {
+                    Some((hex_key, sealed)) => Share::open(sealed, &decode_custodian_key(hex_key)),
+                    None => Share::from_printable(arg),
+                })
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|e| fail(e.to_string()));
+
+            let service = CryptService::from_shares(&shares)
+                .unwrap_or_else(|e| fail(e.to_string()));
+            write_service(key_file, &service);
+            println!("Anahtar materyali geri yüklendi: {}", key_file);
+        }
+        ["import-legacy", key_file, rest @ ..] if rest.len() <= 1 => {
+            let lines = read_lines(rest.first().copied().unwrap_or("-"));
+            let [enc_key, mac_key] = lines.as_slice() else {
+                fail("İki eski anahtar bekleniyor: AES-CTR ve HMAC".to_string());
+            };
+            let keys = LegacyKeys { enc_key: decode_legacy_key(enc_key), mac_key: decode_legacy_key(mac_key) };
+
+            let mut service = load_service(key_file);
+            service.set_legacy_keys(keys);
+            write_service(key_file, &service);
+            println!("Eski anahtarlar eklendi: {}", key_file);
+        }
+        _ => usage(),
+    }
+}
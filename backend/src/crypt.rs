@@ -1,4 +1,4 @@
-use rsa::{RsaPrivateKey, RsaPublicKey, pkcs8::EncodePublicKey};
+use rsa::{RsaPrivateKey, RsaPublicKey, pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey}};
 use rsa::Pkcs1v15Encrypt;
 use aes_gcm::{
     aead::{Aead, KeyInit},
@@ -7,7 +7,15 @@ use aes_gcm::{
 use rand::{thread_rng, RngCore};
 use serde::{Serialize, Deserialize};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use std::env;
 use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::keywrap;
+use crate::legacy::LegacyKeys;
+
+// Servislerin yükleyeceği anahtar materyali dosyası (crypt-admin generate/restore çıktısı)
+pub const KEY_MATERIAL_FILE_ENV: &str = "KEY_MATERIAL_FILE";
 
 #[derive(Debug)]
 pub enum CryptError {
@@ -24,16 +32,49 @@ impl fmt::Display for CryptError {
 
 impl std::error::Error for CryptError {}
 
+pub(crate) fn missing_key(name: &str) -> CryptError {
+    CryptError::CryptFailed(format!("{} key not configured; load key material via {}", name, KEY_MATERIAL_FILE_ENV))
+}
+
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct EncryptedData {
     pub encrypted_key: String,    // RSA ile şifrelenmiş AES anahtarı (base64)
     pub nonce: String,           // AES nonce (base64)
     pub data: String,            // AES ile şifrelenmiş veri (base64)
 }
 
+// KEY_MATERIAL_FILE içeriği: servislerin paylaştığı tüm anahtar materyali
+#[derive(Serialize, Deserialize)]
+struct KeyMaterial {
+    private_key: String,         // PKCS#8 DER (base64)
+    fpe_key: String,
+    deterministic_key: String,
+    blind_index_key: String,
+    // Eski sistemin AES-CTR ve HMAC anahtarları; yalnızca crypt-admin import-legacy ile eklenir
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    legacy_enc_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    legacy_mac_key: Option<String>,
+}
+
 pub struct CryptService {
     private_key: RsaPrivateKey,
     public_key: RsaPublicKey,
+    // Simetrik anahtarlar; tokenlar, deterministik şifreli metinler ve kör indeksler yeniden
+    // başlatmalar ve servisler arasında eşleşsin diye yalnızca anahtar materyalinden gelir,
+    // süreç başına üretilmez
+    pub(crate) fpe_key: Option<[u8; 32]>,    // FF1 tokenizasyon anahtarı
+    pub(crate) deterministic_key: Option<[u8; 32]>,
+    pub(crate) blind_index_key: Option<[u8; 32]>,
+    pub(crate) legacy_keys: Option<LegacyKeys>,     // eski zarfların çözümü için
+    key_material_loaded: bool,     // from_key_material ile yüklendi ve doğrulandı
+}
+
+impl Default for CryptService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CryptService {
@@ -41,13 +82,108 @@ impl CryptService {
         let mut rng = rand::thread_rng();
         let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate private key");
         let public_key = RsaPublicKey::from(&private_key);
-        
+
         Self {
             private_key,
             public_key,
+            fpe_key: None,
+            deterministic_key: None,
+            blind_index_key: None,
+            legacy_keys: None,
+            key_material_loaded: false,
+        }
+    }
+
+    // crypt-admin generate: dosyaya yazılacak eksiksiz yeni anahtar materyali
+    pub fn generate() -> Self {
+        let random_key = || {
+            let mut key = [0u8; 32];
+            thread_rng().fill_bytes(&mut key);
+            Some(key)
+        };
+
+        Self {
+            fpe_key: random_key(),
+            deterministic_key: random_key(),
+            blind_index_key: random_key(),
+            ..Self::new()
+        }
+    }
+
+    pub fn export_key_material(&self) -> Result<Vec<u8>, CryptError> {
+        let private_key = self.private_key.to_pkcs8_der()
+            .map_err(|e| CryptError::CryptFailed(format!("Private key encode error: {}", e)))?;
+
+        let material = KeyMaterial {
+            private_key: BASE64.encode(private_key.as_bytes()),
+            fpe_key: BASE64.encode(self.fpe_key.ok_or_else(|| missing_key("FPE"))?),
+            deterministic_key: BASE64.encode(self.deterministic_key.ok_or_else(|| missing_key("Deterministic"))?),
+            blind_index_key: BASE64.encode(self.blind_index_key.ok_or_else(|| missing_key("Blind index"))?),
+            legacy_enc_key: self.legacy_keys.as_ref().map(|keys| BASE64.encode(keys.enc_key)),
+            legacy_mac_key: self.legacy_keys.as_ref().map(|keys| BASE64.encode(keys.mac_key)),
+        };
+
+        serde_json::to_vec(&material)
+            .map_err(|e| CryptError::CryptFailed(format!("JSON serialize error: {}", e)))
+    }
+
+    // KEY_MATERIAL_FILE verilmişse anahtarlar o dosyadan yüklenir, dosya okunamazsa hata
+    // döner. Verilmemişse süreç başına yeni anahtarlar üretilir (yalnızca geliştirme için)
+    pub fn from_env() -> Result<Self, CryptError> {
+        match env::var(KEY_MATERIAL_FILE_ENV) {
+            Ok(path) => Self::load(Path::new(&path)),
+            Err(_) => Ok(Self::new()),
         }
     }
 
+    pub fn load(path: &Path) -> Result<Self, CryptError> {
+        let material = fs::read(path)
+            .map_err(|e| CryptError::CryptFailed(format!("Key material file {} unreadable: {}", path.display(), e)))?;
+        Self::from_key_material(&material)
+    }
+
+    pub fn from_key_material(material: &[u8]) -> Result<Self, CryptError> {
+        let material: KeyMaterial = serde_json::from_slice(material)
+            .map_err(|e| CryptError::CryptFailed(format!("Invalid key material: {}", e)))?;
+
+        let decode_key = |encoded: &str| -> Result<[u8; 32], CryptError> {
+            BASE64.decode(encoded)
+                .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?
+                .try_into()
+                .map_err(|_| CryptError::CryptFailed("Invalid symmetric key length".to_string()))
+        };
+
+        let private_key_der = BASE64.decode(&material.private_key)
+            .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+        let private_key = RsaPrivateKey::from_pkcs8_der(&private_key_der)
+            .map_err(|e| CryptError::CryptFailed(format!("Private key decode error: {}", e)))?;
+        private_key.validate()
+            .map_err(|e| CryptError::CryptFailed(format!("Invalid private key: {}", e)))?;
+        let public_key = RsaPublicKey::from(&private_key);
+
+        let legacy_keys = match (&material.legacy_enc_key, &material.legacy_mac_key) {
+            (Some(enc_key), Some(mac_key)) => Some(LegacyKeys { enc_key: decode_key(enc_key)?, mac_key: decode_key(mac_key)? }),
+            (None, None) => None,
+            _ => return Err(CryptError::CryptFailed("Legacy keys must be given together".to_string())),
+        };
+
+        Ok(Self {
+            private_key,
+            public_key,
+            fpe_key: Some(decode_key(&material.fpe_key)?),
+            deterministic_key: Some(decode_key(&material.deterministic_key)?),
+            blind_index_key: Some(decode_key(&material.blind_index_key)?),
+            legacy_keys,
+            key_material_loaded: true,
+        })
+    }
+
+    // Hazır olma kontrolü: anahtar materyali yapılandırmadan yüklendi mi. Doğrulama
+    // yüklemede bir kez yapılır; her sorguda RSA anahtarı yeniden doğrulanmaz
+    pub fn has_key_material(&self) -> bool {
+        self.key_material_loaded
+    }
+
     pub fn get_public_key(&self) -> String {
         self.public_key.to_public_key_pem(rsa::pkcs8::LineEnding::LF)
             .expect("failed to encode public key")
@@ -95,6 +231,9 @@ impl CryptService {
             .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
         let encrypted_data = BASE64.decode(&encrypted.data)
             .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+        if nonce.len() != 12 {
+            return Err(CryptError::CryptFailed(format!("Invalid nonce length: {} bytes", nonce.len())));
+        }
 
         // RSA ile AES anahtarını çöz
         let aes_key = self.private_key
@@ -112,6 +251,37 @@ impl CryptService {
         String::from_utf8(decrypted)
             .map_err(|_| CryptError::CryptFailed("Invalid UTF-8 in decrypted data".to_string()))
     }
+
+    // Zarftaki AES anahtarını KMS anahtarı (KEK) ile RFC 3394 formatında dışa aktarır
+    pub fn export_data_key(&self, encrypted: &EncryptedData, kek: &[u8]) -> Result<String, CryptError> {
+        let encrypted_key = BASE64.decode(&encrypted.encrypted_key)
+            .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+
+        let aes_key = self.private_key
+            .decrypt(Pkcs1v15Encrypt, &encrypted_key)
+            .map_err(|e| CryptError::CryptFailed(format!("RSA decryption error: {}", e)))?;
+
+        let wrapped = keywrap::wrap_key(kek, &aes_key)?;
+        Ok(BASE64.encode(wrapped))
+    }
+
+    // KMS'ten gelen sarılı anahtarı açar ve EncryptedData.encrypted_key olarak kullanılabilecek
+    // şekilde RSA ile yeniden şifreler
+    pub fn import_data_key(&self, wrapped_key: &str, kek: &[u8]) -> Result<String, CryptError> {
+        let wrapped = BASE64.decode(wrapped_key)
+            .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+
+        let aes_key = keywrap::unwrap_key(kek, &wrapped)?;
+        if aes_key.len() != 32 {
+            return Err(CryptError::CryptFailed(format!("Invalid data key length: {} bytes", aes_key.len())));
+        }
+
+        let encrypted_key = self.public_key
+            .encrypt(&mut thread_rng(), Pkcs1v15Encrypt, &aes_key)
+            .map_err(|e| CryptError::CryptFailed(format!("RSA encryption error: {}", e)))?;
+
+        Ok(BASE64.encode(encrypted_key))
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +299,33 @@ mod tests {
         assert_eq!(original_data, decrypted);
     }
 
+    #[test]
+    fn test_has_key_material() {
+        let generated = CryptService::generate();
+        assert!(!generated.has_key_material());
+
+        let loaded = CryptService::from_key_material(&generated.export_key_material().unwrap()).unwrap();
+        assert!(loaded.has_key_material());
+
+        let service = CryptService::new();
+        assert!(!service.has_key_material());
+        assert!(service.export_key_material().is_err());
+    }
+
+    #[test]
+    fn test_load_key_material_file() {
+        let service = CryptService::generate();
+        let path = std::env::temp_dir().join(format!("crypt-key-{}.json", std::process::id()));
+        fs::write(&path, service.export_key_material().unwrap()).unwrap();
+
+        let loaded = CryptService::load(&path).expect("Yükleme başarısız");
+        let encrypted = service.encrypt_data("kalıcı anahtar").unwrap();
+        assert_eq!(loaded.decrypt_data(&encrypted).unwrap(), "kalıcı anahtar");
+
+        fs::remove_file(&path).unwrap();
+        assert!(CryptService::load(&path).is_err());
+    }
+
     #[test]
     fn test_long_message() {
         let service = CryptService::new();
@@ -150,4 +347,22 @@ mod tests {
         
         assert_eq!(special_chars, decrypted);
     }
+
+    #[test]
+    fn test_export_import_data_key() {
+        let service = CryptService::new();
+        let kek = [9u8; 32];
+
+        let encrypted = service.encrypt_data("KMS ile taşınan veri").unwrap();
+        let wrapped = service.export_data_key(&encrypted, &kek).expect("Dışa aktarma başarısız");
+
+        let reimported = EncryptedData {
+            encrypted_key: service.import_data_key(&wrapped, &kek).expect("İçe aktarma başarısız"),
+            nonce: encrypted.nonce.clone(),
+            data: encrypted.data.clone(),
+        };
+
+        assert_eq!(service.decrypt_data(&reimported).unwrap(), "KMS ile taşınan veri");
+        assert!(service.import_data_key(&wrapped, &[8u8; 32]).is_err());
+    }
 }
@@ -0,0 +1,213 @@
+// Eşitlik araması için deterministik şifreleme ve kör indeks (blind index).
+//
+// UYARI: Bu moddaki şifreli metinler ve indeksler, aynı bağlamdaki eşit düz metinleri
+// açığa çıkarır. Saldırgan hangi kayıtların aynı değeri taşıdığını, değer dağılımını
+// (frekans analizi) ve tahmin edilebilir değerler için sözlük eşleşmesini görebilir.
+// Yalnızca eşitlik araması gereken alanlarda kullanılmalıdır.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use serde::{Serialize, Deserialize};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use crate::crypt::{missing_key, CryptError, CryptService};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const LEAKAGE_WARNING: &str =
+    "Deterministic encryption reveals which records share the same plaintext within a context";
+
+pub const MIN_BLIND_INDEX_BYTES: usize = 4;
+pub const DEFAULT_BLIND_INDEX_BYTES: usize = 16;
+
+/// Deterministik modu açıkça seçmek için gereken işaret.
+/// Yalnızca `accept_equality_leakage` ile oluşturulabilir; böylece çağıran kodda
+/// sızıntının kabul edildiği görünür olur.
+#[derive(Debug, Clone, Copy)]
+pub struct DeterministicMode {
+    _private: (),
+}
+
+impl DeterministicMode {
+    /// Eşit düz metinlerin eşit şifreli metin üreteceğini kabul eder.
+    pub fn accept_equality_leakage() -> Self {
+        Self { _private: () }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeterministicEncryptedData {
+    pub mode: String,            // her zaman "deterministic"
+    pub nonce: String,           // HMAC'ten türetilen sentetik nonce (base64)
+    pub data: String,            // AES-GCM ile şifrelenmiş veri (base64)
+}
+
+const MODE_NAME: &str = "deterministic";
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> Result<[u8; 32], CryptError> {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(key)
+        .map_err(|e| CryptError::CryptFailed(format!("HMAC key error: {}", e)))?;
+    for part in parts {
+        // Uzunluk öneki, bağlam/değer sınırının kaydırılmasını engeller
+        mac.update(&(part.len() as u64).to_be_bytes());
+        mac.update(part);
+    }
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// Anahtarlı kör indeks: HMAC-SHA256(key, context || value) ilk `length` byte (hex).
+/// Kısa indeksler çakışma üretir (arama sonuçları tekrar filtrelenmelidir) ama
+/// daha az bilgi sızdırır.
+pub fn blind_index(key: &[u8], context: &str, value: &str, length: usize) -> Result<String, CryptError> {
+    if !(MIN_BLIND_INDEX_BYTES..=32).contains(&length) {
+        return Err(CryptError::CryptFailed(format!("Invalid blind index length: {} bytes", length)));
+    }
+
+    let digest = hmac(key, &[context.as_bytes(), value.as_bytes()])?;
+    Ok(hex::encode(&digest[..length]))
+}
+
+impl CryptService {
+    fn deterministic_subkey(&self, label: &str) -> Result<[u8; 32], CryptError> {
+        let key = self.deterministic_key.as_ref().ok_or_else(|| missing_key("Deterministic"))?;
+        hmac(key, &[label.as_bytes()])
+    }
+
+    /// Aynı `context` ve `data` için her zaman aynı çıktıyı üretir (bkz. `LEAKAGE_WARNING`).
+    /// `context` (ör. tablo.kolon) ek doğrulanmış veri olarak bağlanır; farklı
+    /// bağlamlardaki eşit değerler eşleşmez.
+    pub fn encrypt_deterministic(
+        &self,
+        _mode: DeterministicMode,
+        context: &str,
+        data: &str,
+    ) -> Result<DeterministicEncryptedData, CryptError> {
+        let nonce_key = self.deterministic_subkey("nonce")?;
+        let enc_key = self.deterministic_subkey("enc")?;
+
+        let digest = hmac(&nonce_key, &[context.as_bytes(), data.as_bytes()])?;
+        let nonce = &digest[..12];
+
+        let cipher = Aes256Gcm::new_from_slice(&enc_key)
+            .map_err(|e| CryptError::CryptFailed(format!("AES key error: {}", e)))?;
+        let encrypted = cipher
+            .encrypt(Nonce::from_slice(nonce), Payload { msg: data.as_bytes(), aad: context.as_bytes() })
+            .map_err(|e| CryptError::CryptFailed(format!("AES encryption error: {}", e)))?;
+
+        Ok(DeterministicEncryptedData {
+            mode: MODE_NAME.to_string(),
+            nonce: BASE64.encode(nonce),
+            data: BASE64.encode(encrypted),
+        })
+    }
+
+    pub fn decrypt_deterministic(
+        &self,
+        context: &str,
+        encrypted: &DeterministicEncryptedData,
+    ) -> Result<String, CryptError> {
+        if encrypted.mode != MODE_NAME {
+            return Err(CryptError::CryptFailed(format!("Unsupported mode: {}", encrypted.mode)));
+        }
+
+        let nonce = BASE64.decode(&encrypted.nonce)
+            .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+        let data = BASE64.decode(&encrypted.data)
+            .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+        if nonce.len() != 12 {
+            return Err(CryptError::CryptFailed(format!("Invalid nonce length: {} bytes", nonce.len())));
+        }
+
+        let enc_key = self.deterministic_subkey("enc")?;
+        let cipher = Aes256Gcm::new_from_slice(&enc_key)
+            .map_err(|e| CryptError::CryptFailed(format!("AES key error: {}", e)))?;
+        let decrypted = cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &data, aad: context.as_bytes() })
+            .map_err(|e| CryptError::CryptFailed(format!("AES decryption error: {}", e)))?;
+
+        let plaintext = String::from_utf8(decrypted)
+            .map_err(|_| CryptError::CryptFailed("Invalid UTF-8 in decrypted data".to_string()))?;
+
+        // Sentetik nonce düz metinden yeniden hesaplanıp doğrulanır
+        let nonce_key = self.deterministic_subkey("nonce")?;
+        let expected = hmac(&nonce_key, &[context.as_bytes(), plaintext.as_bytes()])?;
+        if expected[..12] != nonce[..] {
+            return Err(CryptError::CryptFailed("Synthetic nonce mismatch".to_string()));
+        }
+
+        Ok(plaintext)
+    }
+
+    /// Servisin kör indeks anahtarı ile `blind_index` (bkz. `LEAKAGE_WARNING`).
+    pub fn blind_index(&self, context: &str, value: &str, length: usize) -> Result<String, CryptError> {
+        let key = self.blind_index_key.as_ref().ok_or_else(|| missing_key("Blind index"))?;
+        blind_index(key, context, value, length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_encryption() {
+        let service = CryptService::generate();
+        let mode = DeterministicMode::accept_equality_leakage();
+
+        let first = service.encrypt_deterministic(mode, "users.email", "ayse@example.com").unwrap();
+        let second = service.encrypt_deterministic(mode, "users.email", "ayse@example.com").unwrap();
+        let other_value = service.encrypt_deterministic(mode, "users.email", "mehmet@example.com").unwrap();
+        let other_context = service.encrypt_deterministic(mode, "users.backup_email", "ayse@example.com").unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other_value);
+        assert_ne!(first.data, other_context.data);
+
+        assert_eq!(service.decrypt_deterministic("users.email", &first).unwrap(), "ayse@example.com");
+        assert!(service.decrypt_deterministic("users.backup_email", &first).is_err());
+    }
+
+    #[test]
+    fn test_blind_index() {
+        let key = [4u8; 32];
+
+        let index = blind_index(&key, "users.email", "ayse@example.com", DEFAULT_BLIND_INDEX_BYTES).unwrap();
+        assert_eq!(index.len(), DEFAULT_BLIND_INDEX_BYTES * 2);
+        assert_eq!(index, blind_index(&key, "users.email", "ayse@example.com", DEFAULT_BLIND_INDEX_BYTES).unwrap());
+        assert_ne!(index, blind_index(&key, "users.phone", "ayse@example.com", DEFAULT_BLIND_INDEX_BYTES).unwrap());
+        assert_ne!(index, blind_index(&[5u8; 32], "users.email", "ayse@example.com", DEFAULT_BLIND_INDEX_BYTES).unwrap());
+
+        // Bağlam ile değer arasındaki sınır kaydırılamaz
+        assert_ne!(
+            blind_index(&key, "ab", "c", 8).unwrap(),
+            blind_index(&key, "a", "bc", 8).unwrap()
+        );
+
+        assert!(blind_index(&key, "users.email", "x", 2).is_err());
+        assert!(blind_index(&key, "users.email", "x", 33).is_err());
+    }
+
+    // Aynı anahtar materyalini yükleyen süreçler aynı şifreli metni ve indeksi üretir
+    #[test]
+    fn test_matches_across_restarts() {
+        let mode = DeterministicMode::accept_equality_leakage();
+        let service = CryptService::generate();
+        let restarted = CryptService::from_key_material(&service.export_key_material().unwrap()).unwrap();
+
+        assert_eq!(
+            service.encrypt_deterministic(mode, "users.email", "ayse@example.com").unwrap(),
+            restarted.encrypt_deterministic(mode, "users.email", "ayse@example.com").unwrap()
+        );
+        assert_eq!(
+            service.blind_index("users.email", "ayse@example.com", DEFAULT_BLIND_INDEX_BYTES).unwrap(),
+            restarted.blind_index("users.email", "ayse@example.com", DEFAULT_BLIND_INDEX_BYTES).unwrap()
+        );
+
+        let unloaded = CryptService::new();
+        assert!(unloaded.encrypt_deterministic(mode, "users.email", "ayse@example.com").is_err());
+        assert!(unloaded.blind_index("users.email", "ayse@example.com", DEFAULT_BLIND_INDEX_BYTES).is_err());
+    }
+}
@@ -0,0 +1,408 @@
+// JSON dokümanlarında alan bazlı şifreleme.
+// Seçilen her değer, yerinde {"$enc": EncryptedData} zarfı ile değiştirilir.
+// Desteklenen yol sözdizimi: $.a.b, $.a[0], $.a[*], $['a']
+
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use std::fmt;
+use crate::crypt::{CryptError, CryptService, EncryptedData};
+
+const ENVELOPE_KEY: &str = "$enc";
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FieldCryptRequest {
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub document: Value,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(path: &str) -> Result<Self, CryptError> {
+        let invalid = |reason: &str| CryptError::CryptFailed(format!("Invalid JSON path '{}': {}", path, reason));
+
+        let rest = path.strip_prefix('$').ok_or_else(|| invalid("must start with '$'"))?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut segments = Vec::new();
+        let mut i = 0;
+
+        while i < chars.len() {
+            match chars[i] {
+                '.' => {
+                    let start = i + 1;
+                    let mut end = start;
+                    while end < chars.len() && chars[end] != '.' && chars[end] != '[' {
+                        end += 1;
+                    }
+                    if start == end {
+                        return Err(invalid("empty field name"));
+                    }
+                    let name: String = chars[start..end].iter().collect();
+                    segments.push(if name == "*" { Segment::Wildcard } else { Segment::Key(name) });
+                    i = end;
+                }
+                '[' => {
+                    let close = chars[i..].iter().position(|&c| c == ']')
+                        .map(|p| i + p)
+                        .ok_or_else(|| invalid("unclosed '['"))?;
+                    let inner: String = chars[i + 1..close].iter().collect();
+
+                    let segment = if inner == "*" {
+                        Segment::Wildcard
+                    } else if let Some(name) = inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+                        Segment::Key(name.to_string())
+                    } else {
+                        Segment::Index(inner.parse().map_err(|_| invalid("invalid array index"))?)
+                    };
+                    segments.push(segment);
+                    i = close + 1;
+                }
+                _ => return Err(invalid("unexpected character")),
+            }
+        }
+
+        if segments.is_empty() {
+            return Err(invalid("root document cannot be selected"));
+        }
+
+        Ok(Self { segments })
+    }
+
+    // İki yolun ikisinin de seçtiği alanları seçen yol; ortak alan yoksa None.
+    // Derinlikler eşit olmalıdır; `[*]` karşısındaki segment olduğu gibi alınır
+    pub fn intersect(&self, other: &JsonPath) -> Option<JsonPath> {
+        if self.segments.len() != other.segments.len() {
+            return None;
+        }
+
+        self.segments.iter()
+            .zip(&other.segments)
+            .map(|pair| match pair {
+                (Segment::Wildcard, segment) | (segment, Segment::Wildcard) => Some(segment.clone()),
+                (a, b) if a == b => Some(a.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|segments| Self { segments })
+    }
+
+    // Yoldaki her eşleşme için f çağrılır; bulunamayan alanlar atlanır
+    fn for_each_mut<F>(&self, value: &mut Value, f: &mut F) -> Result<(), CryptError>
+    where
+        F: FnMut(&mut Value) -> Result<(), CryptError>,
+    {
+        visit(value, &self.segments, f)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("$")?;
+        for segment in &self.segments {
+            match segment {
+                Segment::Key(name) if !name.is_empty()
+                    && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') => write!(f, ".{}", name)?,
+                Segment::Key(name) => write!(f, "['{}']", name)?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+                Segment::Wildcard => f.write_str("[*]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn visit<F>(value: &mut Value, segments: &[Segment], f: &mut F) -> Result<(), CryptError>
+where
+    F: FnMut(&mut Value) -> Result<(), CryptError>,
+{
+    let Some((segment, rest)) = segments.split_first() else {
+        return f(value);
+    };
+
+    // Şifrelenmiş bir alanın içine inilmez
+    if is_envelope(value) {
+        return Ok(());
+    }
+
+    match (segment, value) {
+        (Segment::Key(name), Value::Object(map)) => match map.get_mut(name) {
+            Some(child) => visit(child, rest, f),
+            None => Ok(()),
+        },
+        (Segment::Index(index), Value::Array(items)) => match items.get_mut(*index) {
+            Some(child) => visit(child, rest, f),
+            None => Ok(()),
+        },
+        (Segment::Wildcard, Value::Array(items)) => {
+            items.iter_mut().try_for_each(|child| visit(child, rest, f))
+        }
+        (Segment::Wildcard, Value::Object(map)) => {
+            map.values_mut().try_for_each(|child| visit(child, rest, f))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_envelope(value: &Value) -> bool {
+    matches!(value, Value::Object(map) if map.len() == 1 && map.contains_key(ENVELOPE_KEY))
+}
+
+fn parse_paths(paths: &[String]) -> Result<Vec<JsonPath>, CryptError> {
+    paths.iter().map(|p| JsonPath::parse(p)).collect()
+}
+
+// İstenen yolların, çağıranın politikasının izin verdiği yollarla kesişimi.
+// decrypt_fields'e yalnızca bu liste verilmelidir; kesişim dışındaki alanlar şifreli kalır
+pub fn authorized_paths(requested: &[String], allowed: &[String]) -> Result<Vec<String>, CryptError> {
+    let allowed = parse_paths(allowed)?;
+    let mut paths = Vec::new();
+
+    for path in parse_paths(requested)? {
+        for allowed_path in &allowed {
+            if let Some(intersection) = path.intersect(allowed_path).map(|p| p.to_string()) {
+                if !paths.contains(&intersection) {
+                    paths.push(intersection);
+                }
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+// Yollardaki şifreli alanların somut yolları (ör. $.cards[0].pan) ve zarf içerikleri;
+// dokümanı kuyruğa yazmadan doğrulamak için kullanılır
+pub fn envelopes<'a>(document: &'a Value, paths: &[String]) -> Result<Vec<(String, &'a Value)>, CryptError> {
+    let mut found = Vec::new();
+    for path in parse_paths(paths)? {
+        find_envelopes(document, &path.segments, &mut Vec::new(), &mut found);
+    }
+    Ok(found)
+}
+
+fn find_envelopes<'a>(value: &'a Value, segments: &[Segment], at: &mut Vec<Segment>, found: &mut Vec<(String, &'a Value)>) {
+    if is_envelope(value) {
+        let path = JsonPath { segments: at.clone() }.to_string();
+        if segments.is_empty() && !found.iter().any(|(p, _)| *p == path) {
+            found.push((path, &value[ENVELOPE_KEY]));
+        }
+        return;
+    }
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+
+    let children: Vec<(Segment, &Value)> = match (segment, value) {
+        (Segment::Key(name), Value::Object(map)) => map.get(name)
+            .map(|child| (segment.clone(), child))
+            .into_iter()
+            .collect(),
+        (Segment::Index(index), Value::Array(items)) => items.get(*index)
+            .map(|child| (segment.clone(), child))
+            .into_iter()
+            .collect(),
+        (Segment::Wildcard, Value::Array(items)) => items.iter()
+            .enumerate()
+            .map(|(i, child)| (Segment::Index(i), child))
+            .collect(),
+        (Segment::Wildcard, Value::Object(map)) => map.iter()
+            .map(|(name, child)| (Segment::Key(name.clone()), child))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    for (segment, child) in children {
+        at.push(segment);
+        find_envelopes(child, rest, at, found);
+        at.pop();
+    }
+}
+
+impl CryptService {
+    // Değerin orijinal JSON tipi korunur; çözme sırasında aynen geri yüklenir
+    pub fn encrypt_fields(&self, document: &mut Value, paths: &[String]) -> Result<(), CryptError> {
+        for path in parse_paths(paths)? {
+            path.for_each_mut(document, &mut |field| {
+                if is_envelope(field) {
+                    return Ok(());
+                }
+
+                let plaintext = serde_json::to_string(field)
+                    .map_err(|e| CryptError::CryptFailed(format!("JSON serialize error: {}", e)))?;
+                let encrypted = serde_json::to_value(self.encrypt_data(&plaintext)?)
+                    .map_err(|e| CryptError::CryptFailed(format!("JSON serialize error: {}", e)))?;
+
+                let mut envelope = serde_json::Map::new();
+                envelope.insert(ENVELOPE_KEY.to_string(), encrypted);
+                *field = Value::Object(envelope);
+                Ok(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    // Yalnızca verilen yollardaki zarflar çözülür; yetki kontrolü çağıranın işidir (bkz. authorized_paths)
+    pub fn decrypt_fields(&self, document: &mut Value, authorized_paths: &[String]) -> Result<(), CryptError> {
+        for path in parse_paths(authorized_paths)? {
+            path.for_each_mut(document, &mut |field| {
+                if !is_envelope(field) {
+                    return Ok(());
+                }
+
+                let encrypted: EncryptedData = serde_json::from_value(field[ENVELOPE_KEY].take())
+                    .map_err(|e| CryptError::CryptFailed(format!("Invalid field envelope: {}", e)))?;
+                let plaintext = self.decrypt_data(&encrypted)?;
+
+                *field = serde_json::from_str(&plaintext)
+                    .map_err(|e| CryptError::CryptFailed(format!("JSON parse error: {}", e)))?;
+                Ok(())
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_paths() {
+        assert_eq!(
+            JsonPath::parse("$.cards[*].pan").unwrap().segments,
+            vec![Segment::Key("cards".into()), Segment::Wildcard, Segment::Key("pan".into())]
+        );
+        assert_eq!(
+            JsonPath::parse("$['customer'].ids[2]").unwrap().segments,
+            vec![Segment::Key("customer".into()), Segment::Key("ids".into()), Segment::Index(2)]
+        );
+        assert!(JsonPath::parse("customer.ssn").is_err());
+        assert!(JsonPath::parse("$").is_err());
+        assert!(JsonPath::parse("$.a[").is_err());
+        assert!(JsonPath::parse("$.a[x]").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_fields() {
+        let service = CryptService::new();
+        let original = json!({
+            "customer": { "name": "Ayşe", "ssn": "12345678901" },
+            "cards": [ { "pan": "4111111111111111", "exp": "12/29" }, { "pan": 5500000000000004u64 } ],
+            "note": null
+        });
+
+        let mut document = original.clone();
+        service.encrypt_fields(&mut document, &paths(&["$.customer.ssn", "$.cards[*].pan", "$.missing.field"]))
+            .expect("Şifreleme başarısız");
+
+        assert_eq!(document["customer"]["name"], "Ayşe");
+        assert_eq!(document["cards"][0]["exp"], "12/29");
+        assert!(is_envelope(&document["customer"]["ssn"]));
+        assert!(is_envelope(&document["cards"][0]["pan"]));
+        assert!(is_envelope(&document["cards"][1]["pan"]));
+
+        // Yetkisi yalnızca kart numaralarına olan çağıran
+        let mut partial = document.clone();
+        service.decrypt_fields(&mut partial, &paths(&["$.cards[*].pan"])).expect("Çözme başarısız");
+        assert!(is_envelope(&partial["customer"]["ssn"]));
+        assert_eq!(partial["cards"], original["cards"]);
+
+        service.decrypt_fields(&mut document, &paths(&["$.customer.ssn", "$.cards[*].pan"]))
+            .expect("Çözme başarısız");
+        assert_eq!(document, original);
+    }
+
+    #[test]
+    fn test_authorized_paths() {
+        let allowed = paths(&["$.cards[*].pan", "$.customer.name", "$['odd key'][*]"]);
+
+        assert_eq!(
+            authorized_paths(&paths(&["$.cards[1].pan", "$.cards[*].exp", "$.customer.ssn"]), &allowed).unwrap(),
+            paths(&["$.cards[1].pan"])
+        );
+        assert_eq!(
+            authorized_paths(&paths(&["$.*.name", "$.cards[*]", "$['odd key'].x"]), &allowed).unwrap(),
+            paths(&["$.customer.name", "$['odd key'].name", "$['odd key'].x"])
+        );
+        assert!(authorized_paths(&paths(&["$.customer.ssn"]), &[]).unwrap().is_empty());
+        assert!(authorized_paths(&paths(&["$.a"]), &paths(&["a"])).is_err());
+    }
+
+    // İstekte listelense de politikanın izin vermediği alan şifreli kalır
+    #[test]
+    fn test_path_outside_policy_stays_encrypted() {
+        let service = CryptService::new();
+        let original = json!({ "customer": { "ssn": "12345678901" }, "cards": [ { "pan": "4111111111111111" } ] });
+
+        let mut document = original.clone();
+        service.encrypt_fields(&mut document, &paths(&["$.customer.ssn", "$.cards[*].pan"])).unwrap();
+
+        let requested = paths(&["$.customer.ssn", "$.cards[*].pan"]);
+        let authorized = authorized_paths(&requested, &paths(&["$.cards[*].pan"])).unwrap();
+        service.decrypt_fields(&mut document, &authorized).unwrap();
+
+        assert!(is_envelope(&document["customer"]["ssn"]));
+        assert_eq!(document["cards"], original["cards"]);
+    }
+
+    #[test]
+    fn test_envelopes() {
+        let service = CryptService::new();
+        let mut document = json!({ "customer": { "ssn": "1" }, "cards": [ { "pan": "2" }, { "pan": "3" } ], "name": "Ayşe" });
+        service.encrypt_fields(&mut document, &paths(&["$.customer.ssn", "$.cards[*].pan"])).unwrap();
+
+        let found: Vec<String> = envelopes(&document, &paths(&["$.*.ssn", "$.cards[*].pan", "$.name"])).unwrap()
+            .into_iter()
+            .map(|(path, envelope)| {
+                assert!(envelope.get("nonce").is_some());
+                path
+            })
+            .collect();
+        assert_eq!(found, ["$.customer.ssn", "$.cards[0].pan", "$.cards[1].pan"]);
+    }
+
+    // Bozuk zarf işlemciyi düşürmemeli, hata ile dönmelidir
+    #[test]
+    fn test_malformed_envelope_is_rejected() {
+        let service = CryptService::new();
+        let mut document = json!({ "ssn": "12345678901" });
+        service.encrypt_fields(&mut document, &paths(&["$.ssn"])).unwrap();
+
+        document["ssn"][ENVELOPE_KEY]["nonce"] = json!("AAAA");
+        assert!(service.decrypt_fields(&mut document, &paths(&["$.ssn"])).is_err());
+
+        let mut document = json!({ "ssn": { "$enc": { "nonce": "AAAA" } } });
+        assert!(service.decrypt_fields(&mut document, &paths(&["$.ssn"])).is_err());
+    }
+
+    #[test]
+    fn test_encrypt_fields_is_idempotent_for_envelopes() {
+        let service = CryptService::new();
+        let mut document = json!({ "secret": { "nested": [1, 2, 3] } });
+
+        service.encrypt_fields(&mut document, &paths(&["$.secret"])).unwrap();
+        let once = document.clone();
+        service.encrypt_fields(&mut document, &paths(&["$.secret", "$.secret.nested"])).unwrap();
+
+        assert_eq!(document, once);
+    }
+}
@@ -0,0 +1,296 @@
+// NIST SP 800-38G FF1 format korumalı şifreleme (FPE).
+// Kart numarası, TCKN gibi değerler aynı alfabe ve uzunlukta tokenize edilir.
+
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes192, Aes256};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use serde::{Serialize, Deserialize};
+use crate::crypt::{missing_key, CryptError, CryptService};
+
+pub const DIGITS: &str = "0123456789";
+pub const ALPHANUMERIC: &str = "0123456789abcdefghijklmnopqrstuvwxyz";
+
+const ROUNDS: u8 = 10;
+const MIN_DOMAIN_SIZE: u32 = 1_000_000;
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TokenizeRequest {
+    pub value: String,
+    pub alphabet: Option<String>,   // varsayılan: DIGITS
+    pub tweak: Option<String>,      // ör. kayıt tipi veya kiracı kimliği
+}
+
+enum Cipher {
+    Aes128(Aes128),
+    Aes192(Aes192),
+    Aes256(Aes256),
+}
+
+impl Cipher {
+    fn encrypt_block(&self, block: &mut [u8; 16]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Cipher::Aes128(c) => c.encrypt_block(block),
+            Cipher::Aes192(c) => c.encrypt_block(block),
+            Cipher::Aes256(c) => c.encrypt_block(block),
+        }
+    }
+}
+
+pub struct Ff1 {
+    cipher: Cipher,
+    alphabet: Vec<char>,
+}
+
+impl Ff1 {
+    pub fn new(key: &[u8], alphabet: &str) -> Result<Self, CryptError> {
+        let invalid_key = |_| CryptError::CryptFailed("Invalid FF1 key".to_string());
+        let cipher = match key.len() {
+            16 => Aes128::new_from_slice(key).map(Cipher::Aes128).map_err(invalid_key)?,
+            24 => Aes192::new_from_slice(key).map(Cipher::Aes192).map_err(invalid_key)?,
+            32 => Aes256::new_from_slice(key).map(Cipher::Aes256).map_err(invalid_key)?,
+            n => return Err(CryptError::CryptFailed(format!("Invalid FF1 key length: {} bytes", n))),
+        };
+
+        let alphabet: Vec<char> = alphabet.chars().collect();
+        if alphabet.len() < 2 || alphabet.len() > 1 << 16 {
+            return Err(CryptError::CryptFailed(format!("Invalid FF1 radix: {}", alphabet.len())));
+        }
+        for (i, c) in alphabet.iter().enumerate() {
+            if alphabet[..i].contains(c) {
+                return Err(CryptError::CryptFailed(format!("Duplicate character in alphabet: '{}'", c)));
+            }
+        }
+
+        Ok(Self { cipher, alphabet })
+    }
+
+    pub fn encrypt(&self, tweak: &[u8], input: &str) -> Result<String, CryptError> {
+        let numerals = self.to_numerals(input)?;
+        let n = numerals.len();
+        let (u, v) = (n / 2, n - n / 2);
+        let mut a = numerals[..u].to_vec();
+        let mut b = numerals[u..].to_vec();
+        let p = self.header(u, n, tweak.len());
+        let (b_len, d) = self.lengths(v);
+
+        for i in 0..ROUNDS {
+            let m = if i % 2 == 0 { u } else { v };
+            let y = self.round_value(&p, tweak, i, &b, b_len, d);
+            let modulus = BigUint::from(self.radix()).pow(m as u32);
+            let c = (self.num(&a) + y) % &modulus;
+
+            a = b;
+            b = self.str_m(c, m);
+        }
+
+        Ok(self.render_numerals(a.iter().chain(b.iter())))
+    }
+
+    pub fn decrypt(&self, tweak: &[u8], input: &str) -> Result<String, CryptError> {
+        let numerals = self.to_numerals(input)?;
+        let n = numerals.len();
+        let (u, v) = (n / 2, n - n / 2);
+        let mut a = numerals[..u].to_vec();
+        let mut b = numerals[u..].to_vec();
+        let p = self.header(u, n, tweak.len());
+        let (b_len, d) = self.lengths(v);
+
+        for i in (0..ROUNDS).rev() {
+            let m = if i % 2 == 0 { u } else { v };
+            let y = self.round_value(&p, tweak, i, &a, b_len, d);
+            let modulus = BigUint::from(self.radix()).pow(m as u32);
+            let c = (self.num(&b) + &modulus - (y % &modulus)) % &modulus;
+
+            b = a;
+            a = self.str_m(c, m);
+        }
+
+        Ok(self.render_numerals(a.iter().chain(b.iter())))
+    }
+
+    fn radix(&self) -> u32 {
+        self.alphabet.len() as u32
+    }
+
+    fn to_numerals(&self, input: &str) -> Result<Vec<u32>, CryptError> {
+        let numerals = input.chars()
+            .map(|c| self.alphabet.iter().position(|&a| a == c)
+                .map(|p| p as u32)
+                .ok_or_else(|| CryptError::CryptFailed(format!("Character '{}' is not in the FF1 alphabet", c))))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // SP 800-38G Rev.1: radix^minlen >= 1.000.000
+        if numerals.len() < 2 || BigUint::from(self.radix()).pow(numerals.len() as u32) < BigUint::from(MIN_DOMAIN_SIZE) {
+            return Err(CryptError::CryptFailed(format!("Input too short for FF1: {} characters", numerals.len())));
+        }
+        if numerals.len() > u32::MAX as usize {
+            return Err(CryptError::CryptFailed("Input too long for FF1".to_string()));
+        }
+
+        Ok(numerals)
+    }
+
+    fn render_numerals<'a>(&self, numerals: impl Iterator<Item = &'a u32>) -> String {
+        numerals.map(|&n| self.alphabet[n as usize]).collect()
+    }
+
+    // P = [1]¹ || [2]¹ || [1]¹ || [radix]³ || [10]¹ || [u mod 256]¹ || [n]⁴ || [t]⁴
+    fn header(&self, u: usize, n: usize, t: usize) -> [u8; 16] {
+        let mut p = [0u8; 16];
+        p[..3].copy_from_slice(&[1, 2, 1]);
+        p[3..6].copy_from_slice(&self.radix().to_be_bytes()[1..]);
+        p[6] = 10;
+        p[7] = (u % 256) as u8;
+        p[8..12].copy_from_slice(&(n as u32).to_be_bytes());
+        p[12..16].copy_from_slice(&(t as u32).to_be_bytes());
+        p
+    }
+
+    // b = ⌈⌈v·log2(radix)⌉ / 8⌉, d = 4⌈b/4⌉ + 4
+    fn lengths(&self, v: usize) -> (usize, usize) {
+        let max = BigUint::from(self.radix()).pow(v as u32) - BigUint::one();
+        let b = (max.bits() as usize).div_ceil(8);
+        (b, 4 * b.div_ceil(4) + 4)
+    }
+
+    fn num(&self, numerals: &[u32]) -> BigUint {
+        numerals.iter().fold(BigUint::zero(), |acc, &n| acc * self.radix() + n)
+    }
+
+    fn str_m(&self, mut x: BigUint, m: usize) -> Vec<u32> {
+        let radix = BigUint::from(self.radix());
+        let mut out = vec![0u32; m];
+        for slot in out.iter_mut().rev() {
+            *slot = (&x % &radix).to_u32().unwrap_or(0);
+            x /= &radix;
+        }
+        out
+    }
+
+    fn round_value(&self, p: &[u8; 16], tweak: &[u8], round: u8, half: &[u32], b_len: usize, d: usize) -> BigUint {
+        // Q = T || [0]^((-t-b-1) mod 16) || [i]¹ || [NUM_radix(B)]^b
+        let mut q = tweak.to_vec();
+        let pad = (16 - (tweak.len() + b_len + 1) % 16) % 16;
+        q.extend(std::iter::repeat_n(0u8, pad));
+        q.push(round);
+        let num_bytes = self.num(half).to_bytes_be();
+        q.extend(std::iter::repeat_n(0u8, b_len.saturating_sub(num_bytes.len())));
+        q.extend_from_slice(&num_bytes[num_bytes.len().saturating_sub(b_len)..]);
+
+        // R = PRF(P || Q), sıfır IV ile CBC-MAC
+        let mut r = [0u8; 16];
+        for block in std::iter::once(&p[..]).chain(q.chunks(16)) {
+            for (x, y) in r.iter_mut().zip(block) {
+                *x ^= y;
+            }
+            self.cipher.encrypt_block(&mut r);
+        }
+
+        // S = R || CIPH(R ⊕ [1]¹⁶) || CIPH(R ⊕ [2]¹⁶) ... ilk d byte
+        let mut s = r.to_vec();
+        let mut j = 1u128;
+        while s.len() < d {
+            let mut block = r;
+            for (x, y) in block.iter_mut().zip(j.to_be_bytes()) {
+                *x ^= y;
+            }
+            self.cipher.encrypt_block(&mut block);
+            s.extend_from_slice(&block);
+            j += 1;
+        }
+
+        BigUint::from_bytes_be(&s[..d])
+    }
+}
+
+impl CryptService {
+    pub fn tokenize(&self, request: &TokenizeRequest) -> Result<String, CryptError> {
+        let (ff1, tweak) = self.ff1_for(request)?;
+        ff1.encrypt(tweak, &request.value)
+    }
+
+    pub fn detokenize(&self, request: &TokenizeRequest) -> Result<String, CryptError> {
+        let (ff1, tweak) = self.ff1_for(request)?;
+        ff1.decrypt(tweak, &request.value)
+    }
+
+    fn ff1_for<'a>(&self, request: &'a TokenizeRequest) -> Result<(Ff1, &'a [u8]), CryptError> {
+        let alphabet = request.alphabet.as_deref().unwrap_or(DIGITS);
+        let tweak = request.tweak.as_deref().map(str::as_bytes).unwrap_or_default();
+        let key = self.fpe_key.as_ref().ok_or_else(|| missing_key("FPE"))?;
+        Ok((Ff1::new(key, alphabet)?, tweak))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_128: &str = "2B7E151628AED2A6ABF7158809CF4F3C";
+    const KEY_192: &str = "2B7E151628AED2A6ABF7158809CF4F3CEF4359D8D580AA4F";
+    const KEY_256: &str = "2B7E151628AED2A6ABF7158809CF4F3CEF4359D8D580AA4F7F036D6F04FC6A94";
+
+    fn check(key: &str, alphabet: &str, tweak: &str, plaintext: &str, ciphertext: &str) {
+        let ff1 = Ff1::new(&hex::decode(key).unwrap(), alphabet).unwrap();
+        let tweak = hex::decode(tweak).unwrap();
+
+        assert_eq!(ff1.encrypt(&tweak, plaintext).unwrap(), ciphertext);
+        assert_eq!(ff1.decrypt(&tweak, ciphertext).unwrap(), plaintext);
+    }
+
+    // NIST SP 800-38G FF1 örnekleri
+    #[test]
+    fn test_nist_samples() {
+        check(KEY_128, DIGITS, "", "0123456789", "2433477484");
+        check(KEY_128, DIGITS, "39383736353433323130", "0123456789", "6124200773");
+        check(KEY_128, ALPHANUMERIC, "3737373770717273373737", "0123456789abcdefghi", "a9tv40mll9kdu509eum");
+        check(KEY_192, DIGITS, "", "0123456789", "2830668132");
+        check(KEY_256, DIGITS, "", "0123456789", "6657667009");
+        check(KEY_256, ALPHANUMERIC, "3737373770717273373737", "0123456789abcdefghi", "xs8a0azh2avyalyzuwd");
+    }
+
+    #[test]
+    fn test_format_is_preserved() {
+        let ff1 = Ff1::new(&[5u8; 32], DIGITS).unwrap();
+        let pan = "4111111111111111";
+
+        let token = ff1.encrypt(b"card", pan).unwrap();
+        assert_eq!(token.len(), pan.len());
+        assert!(token.chars().all(|c| c.is_ascii_digit()));
+        assert_ne!(token, pan);
+        assert_ne!(ff1.encrypt(b"other", pan).unwrap(), token);
+        assert_eq!(ff1.decrypt(b"card", &token).unwrap(), pan);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let ff1 = Ff1::new(&[5u8; 32], DIGITS).unwrap();
+
+        assert!(ff1.encrypt(b"", "12345").is_err());
+        assert!(ff1.encrypt(b"", "12345a7890").is_err());
+        assert!(Ff1::new(&[5u8; 32], "0").is_err());
+        assert!(Ff1::new(&[5u8; 32], "00123").is_err());
+        assert!(Ff1::new(&[5u8; 10], DIGITS).is_err());
+    }
+
+    // Tokenlar aynı anahtar materyalini yükleyen her süreçte çözülebilir
+    #[test]
+    fn test_tokens_survive_restart() {
+        let request = |value: &str| TokenizeRequest {
+            value: value.to_string(),
+            alphabet: None,
+            tweak: Some("card".to_string()),
+        };
+
+        let service = CryptService::generate();
+        let token = service.tokenize(&request("4111111111111111")).unwrap();
+
+        let restarted = CryptService::from_key_material(&service.export_key_material().unwrap()).unwrap();
+        assert_eq!(restarted.detokenize(&request(&token)).unwrap(), "4111111111111111");
+
+        assert!(CryptService::new().tokenize(&request("4111111111111111")).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+// RFC 3394 (AES Key Wrap) ve RFC 5649 (AES Key Wrap with Padding)
+// KMS ile veri anahtarı alışverişi için kullanılır.
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes192, Aes256};
+use crate::crypt::CryptError;
+
+const DEFAULT_IV: [u8; 8] = [0xA6; 8];
+const PADDING_IV_PREFIX: [u8; 4] = [0xA6, 0x59, 0x59, 0xA6];
+
+enum Kek {
+    Aes128(Aes128),
+    Aes192(Aes192),
+    Aes256(Aes256),
+}
+
+impl Kek {
+    fn new(kek: &[u8]) -> Result<Self, CryptError> {
+        let invalid = |_| CryptError::CryptFailed("Invalid KEK".to_string());
+        match kek.len() {
+            16 => Aes128::new_from_slice(kek).map(Kek::Aes128).map_err(invalid),
+            24 => Aes192::new_from_slice(kek).map(Kek::Aes192).map_err(invalid),
+            32 => Aes256::new_from_slice(kek).map(Kek::Aes256).map_err(invalid),
+            n => Err(CryptError::CryptFailed(format!("Invalid KEK length: {} bytes", n))),
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8; 16]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Kek::Aes128(c) => c.encrypt_block(block),
+            Kek::Aes192(c) => c.encrypt_block(block),
+            Kek::Aes256(c) => c.encrypt_block(block),
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8; 16]) {
+        let block = GenericArray::from_mut_slice(block);
+        match self {
+            Kek::Aes128(c) => c.decrypt_block(block),
+            Kek::Aes192(c) => c.decrypt_block(block),
+            Kek::Aes256(c) => c.decrypt_block(block),
+        }
+    }
+
+    // RFC 3394 bölüm 2.2.1, IV dışarıdan verilir (RFC 5649 AIV kullanır)
+    fn wrap_blocks(&self, iv: [u8; 8], plaintext: &[u8]) -> Vec<u8> {
+        let n = plaintext.len() / 8;
+        let mut a = iv;
+        let mut r = plaintext.to_vec();
+        let mut block = [0u8; 16];
+
+        for j in 0..6 {
+            for i in 0..n {
+                block[..8].copy_from_slice(&a);
+                block[8..].copy_from_slice(&r[i * 8..(i + 1) * 8]);
+                self.encrypt_block(&mut block);
+
+                let t = ((n * j) + i + 1) as u64;
+                a.copy_from_slice(&block[..8]);
+                xor_counter(&mut a, t);
+                r[i * 8..(i + 1) * 8].copy_from_slice(&block[8..]);
+            }
+        }
+
+        let mut out = Vec::with_capacity(r.len() + 8);
+        out.extend_from_slice(&a);
+        out.extend_from_slice(&r);
+        out
+    }
+
+    // RFC 3394 bölüm 2.2.2, bütünlük kontrolü için çözülen IV döndürülür
+    fn unwrap_blocks(&self, ciphertext: &[u8]) -> ([u8; 8], Vec<u8>) {
+        let n = ciphertext.len() / 8 - 1;
+        let mut a = [0u8; 8];
+        a.copy_from_slice(&ciphertext[..8]);
+        let mut r = ciphertext[8..].to_vec();
+        let mut block = [0u8; 16];
+
+        for j in (0..6).rev() {
+            for i in (0..n).rev() {
+                let t = ((n * j) + i + 1) as u64;
+                xor_counter(&mut a, t);
+                block[..8].copy_from_slice(&a);
+                block[8..].copy_from_slice(&r[i * 8..(i + 1) * 8]);
+                self.decrypt_block(&mut block);
+
+                a.copy_from_slice(&block[..8]);
+                r[i * 8..(i + 1) * 8].copy_from_slice(&block[8..]);
+            }
+        }
+
+        (a, r)
+    }
+}
+
+fn xor_counter(a: &mut [u8; 8], t: u64) {
+    for (byte, t_byte) in a.iter_mut().zip(t.to_be_bytes()) {
+        *byte ^= t_byte;
+    }
+}
+
+/// RFC 3394: anahtar uzunluğu 8'in katı ve en az 16 byte olmalıdır.
+pub fn wrap_key(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptError> {
+    if key.len() < 16 || !key.len().is_multiple_of(8) {
+        return Err(CryptError::CryptFailed(format!("Invalid key length for AES-KW: {} bytes", key.len())));
+    }
+
+    Ok(Kek::new(kek)?.wrap_blocks(DEFAULT_IV, key))
+}
+
+pub fn unwrap_key(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, CryptError> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return Err(CryptError::CryptFailed(format!("Invalid wrapped key length: {} bytes", wrapped.len())));
+    }
+
+    let (a, key) = Kek::new(kek)?.unwrap_blocks(wrapped);
+    if a != DEFAULT_IV {
+        return Err(CryptError::CryptFailed("AES-KW integrity check failed".to_string()));
+    }
+
+    Ok(key)
+}
+
+/// RFC 5649: herhangi bir uzunluktaki (1..2^32 byte) anahtarı sarar.
+pub fn wrap_key_with_padding(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptError> {
+    if key.is_empty() || key.len() > u32::MAX as usize {
+        return Err(CryptError::CryptFailed(format!("Invalid key length for AES-KWP: {} bytes", key.len())));
+    }
+
+    let kek = Kek::new(kek)?;
+
+    let mut aiv = [0u8; 8];
+    aiv[..4].copy_from_slice(&PADDING_IV_PREFIX);
+    aiv[4..].copy_from_slice(&(key.len() as u32).to_be_bytes());
+
+    let mut padded = key.to_vec();
+    padded.resize(key.len().div_ceil(8) * 8, 0);
+
+    // Tek blokluk veri doğrudan AES ile şifrelenir (RFC 5649 bölüm 4.1)
+    if padded.len() == 8 {
+        let mut block = [0u8; 16];
+        block[..8].copy_from_slice(&aiv);
+        block[8..].copy_from_slice(&padded);
+        kek.encrypt_block(&mut block);
+        return Ok(block.to_vec());
+    }
+
+    Ok(kek.wrap_blocks(aiv, &padded))
+}
+
+pub fn unwrap_key_with_padding(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, CryptError> {
+    if wrapped.len() < 16 || !wrapped.len().is_multiple_of(8) {
+        return Err(CryptError::CryptFailed(format!("Invalid wrapped key length: {} bytes", wrapped.len())));
+    }
+
+    let kek = Kek::new(kek)?;
+
+    let (aiv, padded) = if wrapped.len() == 16 {
+        let mut block = [0u8; 16];
+        block.copy_from_slice(wrapped);
+        kek.decrypt_block(&mut block);
+
+        let mut aiv = [0u8; 8];
+        aiv.copy_from_slice(&block[..8]);
+        (aiv, block[8..].to_vec())
+    } else {
+        kek.unwrap_blocks(wrapped)
+    };
+
+    let integrity_error = || CryptError::CryptFailed("AES-KWP integrity check failed".to_string());
+
+    if aiv[..4] != PADDING_IV_PREFIX {
+        return Err(integrity_error());
+    }
+
+    let mli = u32::from_be_bytes([aiv[4], aiv[5], aiv[6], aiv[7]]) as usize;
+    if mli == 0 || mli > padded.len() || mli + 8 <= padded.len() {
+        return Err(integrity_error());
+    }
+    if padded[mli..].iter().any(|&b| b != 0) {
+        return Err(integrity_error());
+    }
+
+    let mut key = padded;
+    key.truncate(mli);
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 3394 bölüm 4.1
+    #[test]
+    fn test_rfc3394_128_bit_kek() {
+        let kek = hex::decode("000102030405060708090A0B0C0D0E0F").unwrap();
+        let key = hex::decode("00112233445566778899AABBCCDDEEFF").unwrap();
+        let expected = hex::decode("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5").unwrap();
+
+        let wrapped = wrap_key(&kek, &key).unwrap();
+        assert_eq!(wrapped, expected);
+        assert_eq!(unwrap_key(&kek, &wrapped).unwrap(), key);
+    }
+
+    // RFC 3394 bölüm 4.6
+    #[test]
+    fn test_rfc3394_256_bit_kek_256_bit_key() {
+        let kek = hex::decode("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F").unwrap();
+        let key = hex::decode("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F").unwrap();
+        let expected = hex::decode(
+            "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21"
+        ).unwrap();
+
+        let wrapped = wrap_key(&kek, &key).unwrap();
+        assert_eq!(wrapped, expected);
+        assert_eq!(unwrap_key(&kek, &wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn test_unwrap_rejects_tampered_data() {
+        let kek = [7u8; 32];
+        let mut wrapped = wrap_key(&kek, &[1u8; 32]).unwrap();
+        wrapped[10] ^= 1;
+
+        assert!(unwrap_key(&kek, &wrapped).is_err());
+        assert!(unwrap_key(&[8u8; 32], &wrap_key(&kek, &[1u8; 32]).unwrap()).is_err());
+    }
+
+    // RFC 5649 bölüm 6
+    #[test]
+    fn test_rfc5649_vectors() {
+        let kek = hex::decode("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8").unwrap();
+
+        let key = hex::decode("c37b7e6492584340bed12207808941155068f738").unwrap();
+        let expected = hex::decode("138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a").unwrap();
+        let wrapped = wrap_key_with_padding(&kek, &key).unwrap();
+        assert_eq!(wrapped, expected);
+        assert_eq!(unwrap_key_with_padding(&kek, &wrapped).unwrap(), key);
+
+        let key = hex::decode("466f7250617369").unwrap();
+        let expected = hex::decode("afbeb0f07dfbf5419200f2ccb50bb24f").unwrap();
+        let wrapped = wrap_key_with_padding(&kek, &key).unwrap();
+        assert_eq!(wrapped, expected);
+        assert_eq!(unwrap_key_with_padding(&kek, &wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn test_invalid_lengths() {
+        assert!(wrap_key(&[0u8; 32], &[0u8; 12]).is_err());
+        assert!(wrap_key(&[0u8; 20], &[0u8; 16]).is_err());
+        assert!(wrap_key_with_padding(&[0u8; 32], &[]).is_err());
+        assert!(unwrap_key_with_padding(&[0u8; 32], &[0u8; 12]).is_err());
+    }
+}
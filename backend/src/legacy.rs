@@ -0,0 +1,143 @@
+// Eski sistemden kalan AES-256-CTR + HMAC-SHA256 (encrypt-then-MAC) zarfları.
+// Yalnızca çözme desteklenir; yeni veriler CryptService ile şifrelenmelidir.
+// Anahtarlar anahtar materyali dosyasına crypt-admin import-legacy ile eklenir.
+
+use aes::Aes256;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use serde::{Serialize, Deserialize};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use crate::crypt::{missing_key, CryptError, CryptService};
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LegacyEncryptedData {
+    pub iv: String,     // 16 byte CTR başlangıç bloğu (base64)
+    pub data: String,   // AES-CTR ile şifrelenmiş veri (base64)
+    pub mac: String,    // HMAC-SHA256(iv || data) (base64)
+}
+
+pub struct LegacyKeys {
+    pub enc_key: [u8; 32],
+    pub mac_key: [u8; 32],
+}
+
+pub fn decrypt_legacy(keys: &LegacyKeys, encrypted: &LegacyEncryptedData) -> Result<String, CryptError> {
+    let iv = BASE64.decode(&encrypted.iv)
+        .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+    let mut data = BASE64.decode(&encrypted.data)
+        .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+    let mac = BASE64.decode(&encrypted.mac)
+        .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+
+    if iv.len() != 16 {
+        return Err(CryptError::CryptFailed(format!("Invalid CTR IV length: {} bytes", iv.len())));
+    }
+
+    // Önce MAC doğrulanır, sabit zamanlı karşılaştırma
+    let mut verifier = HmacSha256::new_from_slice(&keys.mac_key)
+        .map_err(|e| CryptError::CryptFailed(format!("HMAC key error: {}", e)))?;
+    verifier.update(&iv);
+    verifier.update(&data);
+    verifier.verify_slice(&mac)
+        .map_err(|_| CryptError::CryptFailed("HMAC verification failed".to_string()))?;
+
+    let mut cipher = Aes256Ctr::new_from_slices(&keys.enc_key, &iv)
+        .map_err(|e| CryptError::CryptFailed(format!("AES key error: {}", e)))?;
+    cipher.apply_keystream(&mut data);
+
+    String::from_utf8(data)
+        .map_err(|_| CryptError::CryptFailed("Invalid UTF-8 in decrypted data".to_string()))
+}
+
+impl CryptService {
+    pub fn decrypt_legacy(&self, encrypted: &LegacyEncryptedData) -> Result<String, CryptError> {
+        let keys = self.legacy_keys.as_ref().ok_or_else(|| missing_key("Legacy"))?;
+        decrypt_legacy(keys, encrypted)
+    }
+
+    // crypt-admin import-legacy: sonraki export_key_material çıktısına dahil edilir
+    pub fn set_legacy_keys(&mut self, keys: LegacyKeys) {
+        self.legacy_keys = Some(keys);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{thread_rng, RngCore};
+
+    // Eski sistemin ürettiği zarfı taklit eder
+    fn legacy_encrypt(keys: &LegacyKeys, plaintext: &str) -> LegacyEncryptedData {
+        let mut iv = [0u8; 16];
+        thread_rng().fill_bytes(&mut iv);
+
+        let mut data = plaintext.as_bytes().to_vec();
+        Aes256Ctr::new(&keys.enc_key.into(), &iv.into()).apply_keystream(&mut data);
+
+        let mut mac = HmacSha256::new_from_slice(&keys.mac_key).unwrap();
+        mac.update(&iv);
+        mac.update(&data);
+
+        LegacyEncryptedData {
+            iv: BASE64.encode(iv),
+            data: BASE64.encode(data),
+            mac: BASE64.encode(mac.finalize().into_bytes()),
+        }
+    }
+
+    fn test_keys() -> LegacyKeys {
+        LegacyKeys { enc_key: [1u8; 32], mac_key: [2u8; 32] }
+    }
+
+    #[test]
+    fn test_decrypt_legacy() {
+        let keys = test_keys();
+        let original = "Eski kayıt öçşğü 123";
+
+        let encrypted = legacy_encrypt(&keys, original);
+        let decrypted = decrypt_legacy(&keys, &encrypted).expect("Çözme başarısız");
+
+        assert_eq!(original, decrypted);
+    }
+
+    #[test]
+    fn test_legacy_keys_in_key_material() {
+        let mut service = CryptService::generate();
+        let encrypted = legacy_encrypt(&test_keys(), "eski kayıt");
+        assert!(service.decrypt_legacy(&encrypted).is_err());
+
+        service.set_legacy_keys(test_keys());
+        let restored = CryptService::from_key_material(&service.export_key_material().unwrap()).unwrap();
+        assert_eq!(restored.decrypt_legacy(&encrypted).unwrap(), "eski kayıt");
+
+        // Eski anahtarı olmayan dosyalar aynen yüklenir
+        let plain = CryptService::from_key_material(&CryptService::generate().export_key_material().unwrap()).unwrap();
+        assert!(plain.decrypt_legacy(&encrypted).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_legacy_rejects_tampered_data() {
+        let keys = test_keys();
+        let mut encrypted = legacy_encrypt(&keys, "değiştirilmemeli");
+
+        let mut data = BASE64.decode(&encrypted.data).unwrap();
+        data[0] ^= 1;
+        encrypted.data = BASE64.encode(data);
+
+        assert!(decrypt_legacy(&keys, &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_legacy_rejects_wrong_mac_key() {
+        let keys = test_keys();
+        let encrypted = legacy_encrypt(&keys, "mesaj");
+        let wrong_keys = LegacyKeys { enc_key: keys.enc_key, mac_key: [3u8; 32] };
+
+        assert!(decrypt_legacy(&wrong_keys, &encrypted).is_err());
+    }
+}
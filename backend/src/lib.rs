@@ -1,4 +1,10 @@
 pub mod crypt;
+pub mod deterministic;
+pub mod field;
+pub mod fpe;
+pub mod keywrap;
+pub mod legacy;
+pub mod shamir;
 
 pub use crypt::*;
 
@@ -0,0 +1,273 @@
+// GF(256) üzerinde Shamir gizli paylaşımı (k-of-n).
+// Servis anahtarının yedeklenmesi ve eşik sayıda pay ile geri yüklenmesi için kullanılır.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce
+};
+use rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use crate::crypt::{CryptError, CryptService};
+
+const PRINTABLE_PREFIX: &str = "crypt-share:v1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    pub threshold: u8,
+    pub index: u8,      // polinomun değerlendirildiği x noktası (1..=255)
+    pub data: Vec<u8>,
+}
+
+// AES indirgeme polinomu x^8 + x^4 + x^3 + x + 1 ile çarpım
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = a >> 7;
+        a = (a << 1) ^ (0x1b & 0u8.wrapping_sub(carry));
+        b >>= 1;
+    }
+    product
+}
+
+// a^254 = a^-1 (a != 0)
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+pub fn split_secret(secret: &[u8], threshold: u8, count: u8) -> Result<Vec<Share>, CryptError> {
+    if threshold < 2 || count < threshold {
+        return Err(CryptError::CryptFailed(format!(
+            "Invalid share parameters: threshold {} of {}", threshold, count
+        )));
+    }
+    if secret.is_empty() {
+        return Err(CryptError::CryptFailed("Secret is empty".to_string()));
+    }
+
+    let mut shares: Vec<Share> = (1..=count)
+        .map(|index| Share { threshold, index, data: Vec::with_capacity(secret.len()) })
+        .collect();
+
+    let mut coefficients = vec![0u8; threshold as usize];
+    for &byte in secret {
+        coefficients[0] = byte;
+        thread_rng().fill_bytes(&mut coefficients[1..]);
+
+        for share in shares.iter_mut() {
+            // Horner yöntemi
+            let y = coefficients.iter().rev().fold(0u8, |acc, &c| gf_mul(acc, share.index) ^ c);
+            share.data.push(y);
+        }
+    }
+
+    coefficients.fill(0);
+    Ok(shares)
+}
+
+pub fn combine_shares(shares: &[Share]) -> Result<Vec<u8>, CryptError> {
+    let first = shares.first()
+        .ok_or_else(|| CryptError::CryptFailed("No shares given".to_string()))?;
+    let threshold = first.threshold as usize;
+
+    // Eşik paydan okunur; tek pay ile "geri yükleme" sessizce yanlış sır üretirdi
+    if threshold < 2 {
+        return Err(CryptError::CryptFailed(format!("Invalid share threshold: {}", threshold)));
+    }
+    if shares.iter().any(|share| share.threshold != first.threshold || share.data.len() != first.data.len()) {
+        return Err(CryptError::CryptFailed("Shares do not belong to the same secret".to_string()));
+    }
+    if shares.len() < threshold {
+        return Err(CryptError::CryptFailed(format!(
+            "Not enough shares: {} of {} required", shares.len(), threshold
+        )));
+    }
+
+    let shares = &shares[..threshold];
+    for (i, share) in shares.iter().enumerate() {
+        if share.index == 0 || shares[..i].iter().any(|s| s.index == share.index) {
+            return Err(CryptError::CryptFailed(format!("Invalid or duplicate share index: {}", share.index)));
+        }
+    }
+
+    // x = 0 noktasında Lagrange interpolasyonu
+    let weights: Vec<u8> = shares.iter()
+        .map(|share| {
+            shares.iter()
+                .filter(|other| other.index != share.index)
+                .fold(1u8, |acc, other| {
+                    gf_mul(acc, gf_mul(other.index, gf_inv(other.index ^ share.index)))
+                })
+        })
+        .collect();
+
+    let secret = (0..first.data.len())
+        .map(|pos| {
+            shares.iter()
+                .zip(&weights)
+                .fold(0u8, |acc, (share, &weight)| acc ^ gf_mul(share.data[pos], weight))
+        })
+        .collect();
+
+    Ok(secret)
+}
+
+impl Share {
+    // Kağıda basılabilir biçim: crypt-share:v1:<eşik>:<indeks>:<base64>:<sağlama>
+    pub fn to_printable(&self) -> String {
+        let body = format!("{}:{}:{}:{}", PRINTABLE_PREFIX, self.threshold, self.index, BASE64.encode(&self.data));
+        let checksum = hex::encode(&Sha256::digest(body.as_bytes())[..4]);
+        format!("{}:{}", body, checksum)
+    }
+
+    pub fn from_printable(text: &str) -> Result<Self, CryptError> {
+        let invalid = |reason: &str| CryptError::CryptFailed(format!("Invalid share: {}", reason));
+
+        let text = text.trim();
+        let (body, checksum) = text.rsplit_once(':').ok_or_else(|| invalid("missing checksum"))?;
+        if hex::encode(&Sha256::digest(body.as_bytes())[..4]) != checksum.to_ascii_lowercase() {
+            return Err(invalid("checksum mismatch"));
+        }
+
+        let rest = body.strip_prefix(PRINTABLE_PREFIX)
+            .and_then(|r| r.strip_prefix(':'))
+            .ok_or_else(|| invalid("unknown format"))?;
+        let parts: Vec<&str> = rest.split(':').collect();
+        let [threshold, index, data] = parts[..] else {
+            return Err(invalid("unexpected field count"));
+        };
+
+        Ok(Self {
+            threshold: threshold.parse().map_err(|_| invalid("bad threshold"))?,
+            index: index.parse().map_err(|_| invalid("bad index"))?,
+            data: BASE64.decode(data).map_err(|_| invalid("bad base64"))?,
+        })
+    }
+
+    // Payı sahibinin 32 byte'lık anahtarı ile AES-GCM altında şifreler
+    pub fn seal(&self, custodian_key: &[u8]) -> Result<String, CryptError> {
+        let cipher = Aes256Gcm::new_from_slice(custodian_key)
+            .map_err(|e| CryptError::CryptFailed(format!("AES key error: {}", e)))?;
+
+        let mut nonce = [0u8; 12];
+        thread_rng().fill_bytes(&mut nonce);
+
+        let encrypted = cipher
+            .encrypt(Nonce::from_slice(&nonce), self.to_printable().as_bytes())
+            .map_err(|e| CryptError::CryptFailed(format!("AES encryption error: {}", e)))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&encrypted);
+        Ok(BASE64.encode(sealed))
+    }
+
+    pub fn open(sealed: &str, custodian_key: &[u8]) -> Result<Self, CryptError> {
+        let sealed = BASE64.decode(sealed.trim())
+            .map_err(|e| CryptError::CryptFailed(format!("Base64 decode error: {}", e)))?;
+        if sealed.len() < 12 {
+            return Err(CryptError::CryptFailed("Sealed share is too short".to_string()));
+        }
+
+        let cipher = Aes256Gcm::new_from_slice(custodian_key)
+            .map_err(|e| CryptError::CryptFailed(format!("AES key error: {}", e)))?;
+        let decrypted = cipher
+            .decrypt(Nonce::from_slice(&sealed[..12]), &sealed[12..])
+            .map_err(|e| CryptError::CryptFailed(format!("AES decryption error: {}", e)))?;
+
+        let text = String::from_utf8(decrypted)
+            .map_err(|_| CryptError::CryptFailed("Invalid UTF-8 in decrypted share".to_string()))?;
+        Self::from_printable(&text)
+    }
+}
+
+impl CryptService {
+    // Servisin tüm anahtar materyalini (RSA özel anahtarı dahil) paylara böler
+    pub fn split_key_material(&self, threshold: u8, count: u8) -> Result<Vec<Share>, CryptError> {
+        let mut material = self.export_key_material()?;
+        let shares = split_secret(&material, threshold, count);
+        material.fill(0);
+        shares
+    }
+
+    pub fn from_shares(shares: &[Share]) -> Result<Self, CryptError> {
+        let mut material = combine_shares(shares)?;
+        let service = Self::from_key_material(&material);
+        material.fill(0);
+        service
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine() {
+        let secret = b"gizli anahtar materyali";
+        let shares = split_secret(secret, 3, 5).unwrap();
+
+        assert_eq!(shares.len(), 5);
+        assert_eq!(combine_shares(&shares[..3]).unwrap(), secret);
+        assert_eq!(combine_shares(&[shares[4].clone(), shares[1].clone(), shares[2].clone()]).unwrap(), secret);
+        assert_eq!(combine_shares(&shares).unwrap(), secret);
+
+        assert!(combine_shares(&shares[..2]).is_err());
+        assert!(combine_shares(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err());
+        assert!(split_secret(secret, 1, 5).is_err());
+
+        // Sahte eşik veya eşiği uyuşmayan paylar reddedilir
+        let forged = Share { threshold: 1, ..shares[0].clone() };
+        assert!(combine_shares(std::slice::from_ref(&forged)).is_err());
+        assert!(combine_shares(&[Share { threshold: 0, ..forged.clone() }]).is_err());
+        assert!(combine_shares(&[shares[0].clone(), shares[1].clone(), shares[2].clone(), forged]).is_err());
+        assert!(split_secret(secret, 4, 3).is_err());
+    }
+
+    #[test]
+    fn test_printable_and_sealed_shares() {
+        let shares = split_secret(&[1, 2, 3, 4], 2, 3).unwrap();
+
+        let printable = shares[1].to_printable();
+        assert!(printable.starts_with("crypt-share:v1:2:2:"));
+        assert_eq!(Share::from_printable(&printable).unwrap(), shares[1]);
+
+        let mut typo = printable.clone();
+        typo.replace_range(20..21, "X");
+        assert!(Share::from_printable(&typo).is_err());
+
+        let key = [6u8; 32];
+        let sealed = shares[0].seal(&key).unwrap();
+        assert_eq!(Share::open(&sealed, &key).unwrap(), shares[0]);
+        assert!(Share::open(&sealed, &[7u8; 32]).is_err());
+    }
+
+    #[test]
+    fn test_restore_service_from_shares() {
+        let service = CryptService::generate();
+        let encrypted = service.encrypt_data("yedekten geri yükleme").unwrap();
+
+        let shares = service.split_key_material(2, 3).unwrap();
+        let restored = CryptService::from_shares(&shares[1..]).expect("Geri yükleme başarısız");
+
+        assert_eq!(restored.get_public_key(), service.get_public_key());
+        assert_eq!(restored.decrypt_data(&encrypted).unwrap(), "yedekten geri yükleme");
+    }
+}
@@ -18,7 +18,7 @@ for pid_file in /tmp/dev-*.pid; do
 done
 
 # Kullanılan portları kontrol et ve süreçleri sonlandır
-PORTS=("5173" "8081" "8082" "8083")
+PORTS=("5173" "8081" "8082" "8083" "50051")
 
 for PORT in "${PORTS[@]}"; do
     if sudo lsof -i :"$PORT" >/dev/null 2>&1; then
@@ -41,7 +41,7 @@ wait_for_service() {
     local attempt=1
 
     echo -e "${BLUE}$service_name için bekleniyor...${NC}"
-    while ! nc -z localhost $port; do
+    while ! curl -sf "http://localhost:$port/readyz" > /dev/null; do
         if [ $attempt -eq $max_attempts ]; then
             echo -e "${RED}$service_name başlatılamadı!${NC}"
             exit 1
@@ -66,14 +66,21 @@ if ! command -v cargo &> /dev/null; then
     exit 1
 fi
 
-# netcat kontrol
-if ! command -v nc &> /dev/null; then
-    echo -e "${RED}netcat bulunamadı. Lütfen netcat'i yükleyin.${NC}"
-    echo "sudo apt-get install netcat # Ubuntu/Debian"
-    echo "brew install netcat # macOS"
+# curl kontrol
+if ! command -v curl &> /dev/null; then
+    echo -e "${RED}curl bulunamadı. Lütfen curl'ü yükleyin.${NC}"
+    echo "sudo apt-get install curl # Ubuntu/Debian"
+    echo "brew install curl # macOS"
     exit 1
 fi
 
+# key-gate ve crypt-processor aynı anahtar dosyasını kullanır; yoksa bir kez üretilir
+export KEY_MATERIAL_FILE="${KEY_MATERIAL_FILE:-$PWD/.dev-keys.json}"
+if [ ! -f "$KEY_MATERIAL_FILE" ]; then
+    echo -e "${BLUE}Geliştirme anahtarları üretiliyor: $KEY_MATERIAL_FILE${NC}"
+    cargo run -q -p backend --bin crypt-admin generate "$KEY_MATERIAL_FILE" || exit 1
+fi
+
 # Servisleri paralel başlatma
 echo -e "${GREEN}Backend servisleri başlatılıyor...${NC}"
 (cd services/crypt-gate && RUST_LOG=info cargo run) &
@@ -97,9 +104,10 @@ wait_services() {
         local key_gate_ready=false
         local crypt_processor_ready=false
 
-        nc -z localhost 8081 && crypt_gate_ready=true
-        nc -z localhost 8082 && key_gate_ready=true
-        nc -z localhost 8083 && crypt_processor_ready=true
+        # /readyz tüm bağımlılıklar (kuyruk, tüketiciler, anahtarlar) hazırsa 200 döner
+        curl -sf http://localhost:8081/readyz > /dev/null && crypt_gate_ready=true
+        curl -sf http://localhost:8082/readyz > /dev/null && key_gate_ready=true
+        curl -sf http://localhost:8083/readyz > /dev/null && crypt_processor_ready=true
 
         if $crypt_gate_ready && $key_gate_ready && $crypt_processor_ready; then
             all_ready=true
@@ -146,6 +154,7 @@ echo $FRONTEND_PID > /tmp/dev-frontend.pid
 echo -e "\n${BLUE}Tüm servisler başlatıldı!${NC}"
 echo -e "Frontend: http://localhost:5173"
 echo -e "Crypt Gate: http://localhost:8081"
+echo -e "Crypt Gate gRPC: localhost:50051"
 echo -e "Key Gate: http://localhost:8082"
 echo -e "Crypt Processor: http://localhost:8083"
 
@@ -1,10 +1,27 @@
 import { ref } from 'vue';
+import { useAuthStore } from '../stores/auth';
+
+// WebSocket başlık gönderemez; kimlik HttpOnly oturum çerezi ile taşınır
+const openSession = async () => {
+  const authStore = useAuthStore();
+  const response = await fetch('http://localhost/api/events/session', {
+    method: 'POST',
+    headers: {
+      'Authorization': `Bearer ${authStore.token}`
+    },
+    credentials: 'include'
+  });
+
+  if (!response.ok) {
+    throw new Error('Sonuç oturumu açılamadı');
+  }
+};
 
 export function useWebSocket() {
   const ws = ref(null);
   const messageCallbacks = new Map();
 
-  const connectWebSocket = () => {
+  const connectWebSocket = async () => {
     if (ws.value?.readyState === WebSocket.OPEN) {
       console.log('WebSocket zaten bağlı');
       return;
@@ -12,6 +29,7 @@ export function useWebSocket() {
 
     console.log('WebSocket bağlantısı kuruluyor...');
     try {
+      await openSession();
       ws.value = new WebSocket('ws://localhost/ws/crypt/');
       
       ws.value.onopen = () => {
@@ -0,0 +1,18 @@
+use message_bus::{MemoryBus, MessageBus};
+use std::sync::Arc;
+
+// Geliştirme modu: crypt-gate (8081) ve crypt-processor (8083) tek süreçte,
+// RabbitMQ yerine bellek içi bus ile çalışır. key-gate ayrıca başlatılmalıdır
+#[actix_web::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    telemetry::init();
+    let bus: Arc<dyn MessageBus> = Arc::new(MemoryBus::new());
+    tracing::info!("Bellek içi mesaj kuyruğu ile başlatılıyor");
+
+    let (gate, processor) = tokio::join!(
+        crypt_gate::run(bus.clone()),
+        crypt_processor::run(bus),
+    );
+    gate?;
+    processor
+}
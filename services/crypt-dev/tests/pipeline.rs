@@ -0,0 +1,601 @@
+// crypt-gate → bus → crypt-processor → bus → crypt-gate akışı, broker olmadan
+use actix_web::{http::StatusCode, test, App};
+use backend::crypt::CryptService;
+use backend::legacy::LegacyKeys;
+use chrono::Utc;
+use crypt_gate::grpc::proto::{self, crypt_gate_client::CryptGateClient};
+use crypt_processor::{OperationRegistry, WebSocketManager, WebhookConfig, WebhookSender};
+use jwt_validator::{issue_token, jwt_secret, Claims};
+use message_bus::{JobMessage, MemoryBus, MessageBus, Operation, ENCRYPT_QUEUE, SCHEMA_VERSION};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tonic::{transport::Channel, Code};
+
+fn bearer(sub: &str) -> (&'static str, String) {
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        sub: sub.to_string(),
+        iat: now,
+        exp: now + 3600,
+        tier: "standard".to_string(),
+    };
+    let token = issue_token(&claims, jwt_secret().as_bytes()).unwrap();
+    ("Authorization", format!("Bearer {}", token))
+}
+
+async fn start_processor() -> Arc<dyn MessageBus> {
+    let bus: Arc<dyn MessageBus> = Arc::new(MemoryBus::new());
+    crypt_processor::start_workers(
+        bus.clone(),
+        Arc::new(CryptService::new()),
+        OperationRegistry::default(),
+        Arc::new(WebSocketManager::new()),
+        WebhookSender::new(WebhookConfig::from_env()),
+    ).await.unwrap();
+    bus
+}
+
+#[actix_web::test]
+async fn test_sync_encrypt_decrypt_round_trip() {
+    let bus = start_processor().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(crypt_gate::app_state(bus).await)
+            .configure(crypt_gate::configure)
+    ).await;
+
+    let request = test::TestRequest::post()
+        .uri("/encrypt?mode=sync")
+        .insert_header(bearer("alice"))
+        .insert_header(("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"))
+        .set_json(json!("merhaba dünya"))
+        .to_request();
+    let encrypted: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(encrypted["success"], true);
+    // Trace kuyruk üzerinden processor'a taşınır ve sonuçta geri döner
+    assert!(encrypted["traceparent"].as_str().unwrap().starts_with("00-4bf92f3577b34da6a3ce929d0e0e4736-"));
+
+    let encrypted_data: Value = serde_json::from_str(encrypted["data"].as_str().unwrap()).unwrap();
+    let request = test::TestRequest::post()
+        .uri("/decrypt?mode=sync")
+        .insert_header(bearer("alice"))
+        .set_json(encrypted_data)
+        .to_request();
+    let decrypted: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(decrypted["data"], "merhaba dünya");
+
+    // Gateway ve processor aynı süreçte olduğu için metrikler tek registry'dedir
+    let request = test::TestRequest::get().uri("/metrics").to_request();
+    let metrics = String::from_utf8(test::call_and_read_body(&app, request).await.to_vec()).unwrap();
+    assert!(metrics.contains(r#"jobs_published_total{queue="encrypt_queue"}"#));
+    assert!(metrics.contains(r#"jobs_acked_total{queue="decrypt_queue"}"#));
+    assert!(metrics.contains(r#"crypto_operation_duration_seconds_count{operation="encrypt",outcome="success"}"#));
+}
+
+// Politikanın izin vermediği alan istekte listelense de şifreli kalır
+#[actix_web::test]
+async fn test_decrypt_fields_policy() {
+    std::env::set_var("FIELD_DECRYPT_POLICY", r#"{"subjects": {"alice": ["$.cards[*].pan"]}}"#);
+    let bus = start_processor().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(crypt_gate::app_state(bus).await)
+            .configure(crypt_gate::configure)
+    ).await;
+    let paths = json!(["$.customer.ssn", "$.cards[*].pan"]);
+
+    let request = test::TestRequest::post()
+        .uri("/encrypt-fields?mode=sync")
+        .insert_header(bearer("alice"))
+        .set_json(json!({
+            "document": { "customer": { "ssn": "12345678901" }, "cards": [ { "pan": "4111111111111111" } ] },
+            "paths": paths,
+        }))
+        .to_request();
+    let encrypted: Value = test::call_and_read_body_json(&app, request).await;
+    let document: Value = serde_json::from_str(encrypted["data"].as_str().unwrap()).unwrap();
+
+    let request = test::TestRequest::post()
+        .uri("/decrypt-fields?mode=sync")
+        .insert_header(bearer("alice"))
+        .set_json(json!({ "document": document, "paths": paths }))
+        .to_request();
+    let decrypted: Value = test::call_and_read_body_json(&app, request).await;
+    let document: Value = serde_json::from_str(decrypted["data"].as_str().unwrap()).unwrap();
+
+    assert_eq!(document["cards"][0]["pan"], "4111111111111111");
+    assert!(document["customer"]["ssn"]["$enc"].is_object());
+
+    // Bozuk nonce'lu zarf gateway'de reddedilir, processor'a ulaşmaz
+    let mut tampered = document.clone();
+    tampered["customer"]["ssn"]["$enc"]["nonce"] = json!("AAAA");
+    let request = test::TestRequest::post()
+        .uri("/decrypt-fields?mode=sync")
+        .insert_header(bearer("alice"))
+        .set_json(json!({ "document": tampered, "paths": ["$.customer.ssn"] }))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["details"][0]["field"], "document.customer.ssn.nonce");
+
+    // Politikada yetkisi olmayan kullanıcının isteği kuyruğa yazılmaz
+    let request = test::TestRequest::post()
+        .uri("/decrypt-fields?mode=sync")
+        .insert_header(bearer("bob"))
+        .set_json(json!({ "document": document, "paths": ["$.customer.ssn"] }))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["code"], "FORBIDDEN");
+}
+
+#[actix_web::test]
+async fn test_decrypt_legacy() {
+    let mut crypt_service = CryptService::new();
+    crypt_service.set_legacy_keys(LegacyKeys { enc_key: [1u8; 32], mac_key: [2u8; 32] });
+    let bus: Arc<dyn MessageBus> = Arc::new(MemoryBus::new());
+    crypt_processor::start_workers(
+        bus.clone(),
+        Arc::new(crypt_service),
+        OperationRegistry::default(),
+        Arc::new(WebSocketManager::new()),
+        WebhookSender::new(WebhookConfig::from_env()),
+    ).await.unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(crypt_gate::app_state(bus).await)
+            .configure(crypt_gate::configure)
+    ).await;
+
+    // Eski sistemin ürettiği zarf (AES-256-CTR, ardından HMAC-SHA256(iv || data))
+    let envelope = json!({
+        "iv": "AAECAwQFBgcICQoLDA0ODw==",
+        "data": "npIxhRpyA03nL+Y=",
+        "mac": "I2zSDjhsYUGwrRntN2uNASv4WPkXhjDfMSDX2GkasfY=",
+    });
+    let request = test::TestRequest::post()
+        .uri("/decrypt-legacy?mode=sync")
+        .insert_header(bearer("alice"))
+        .set_json(&envelope)
+        .to_request();
+    let decrypted: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(decrypted["success"], true);
+    assert_eq!(decrypted["data"], "eski kayıt");
+
+    let mut tampered = envelope.clone();
+    tampered["data"] = json!("npIxhRpyA03nL+c=");
+    let request = test::TestRequest::post()
+        .uri("/decrypt-legacy?mode=sync")
+        .insert_header(bearer("alice"))
+        .set_json(tampered)
+        .to_request();
+    let failed: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(failed["success"], false);
+    assert_eq!(failed["code"], "CRYPT_ERROR");
+
+    let mut malformed = envelope;
+    malformed["iv"] = json!("AAAA");
+    let request = test::TestRequest::post()
+        .uri("/decrypt-legacy?mode=sync")
+        .insert_header(bearer("alice"))
+        .set_json(malformed)
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_unknown_operation_fails_job() {
+    let bus = start_processor().await;
+    // Daha yeni bir gateway'in yazdığı, bu processor'ın tanımadığı operasyon
+    let message: JobMessage = serde_json::from_value(json!({
+        "id": "job-1",
+        "operation": "rewrap",
+        "data": "merhaba",
+        "subject": "alice",
+    })).unwrap();
+    assert_eq!(message.operation, Operation::Unknown);
+
+    let reply = bus.request(ENCRYPT_QUEUE, &message, Duration::from_secs(5)).await.unwrap();
+    let reply: Value = serde_json::from_slice(&reply).unwrap();
+    assert_eq!(reply["success"], false);
+    assert_eq!(reply["error"], "Desteklenmeyen operasyon: unknown");
+    assert_eq!(reply["code"], "UNSUPPORTED_OPERATION");
+}
+
+#[actix_web::test]
+async fn test_newer_schema_version_fails_job() {
+    let bus = start_processor().await;
+    let message: JobMessage = serde_json::from_value(json!({
+        "schema_version": SCHEMA_VERSION + 1,
+        "id": "job-1",
+        "operation": "encrypt",
+        "data": "merhaba",
+        "subject": "alice",
+    })).unwrap();
+
+    let reply = bus.request(ENCRYPT_QUEUE, &message, Duration::from_secs(5)).await.unwrap();
+    let reply: Value = serde_json::from_slice(&reply).unwrap();
+    assert_eq!(reply["success"], false);
+    assert_eq!(reply["schema_version"], SCHEMA_VERSION);
+    assert_eq!(reply["code"], "UNSUPPORTED_SCHEMA_VERSION");
+}
+
+#[actix_web::test]
+async fn test_async_job_status() {
+    let bus = start_processor().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(crypt_gate::app_state(bus).await)
+            .configure(crypt_gate::configure)
+    ).await;
+
+    let request = test::TestRequest::post()
+        .uri("/encrypt")
+        .insert_header(bearer("alice"))
+        .set_json(json!("merhaba"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let accepted: Value = test::read_body_json(response).await;
+    let message_id = accepted["message_id"].as_str().unwrap().to_string();
+
+    let mut status = Value::Null;
+    for _ in 0..50 {
+        let request = test::TestRequest::get()
+            .uri(&format!("/jobs/{}", message_id))
+            .insert_header(bearer("alice"))
+            .to_request();
+        status = test::call_and_read_body_json(&app, request).await;
+        if status["status"] != "processing" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(status["status"], "done");
+
+    // Başka kullanıcı işi göremez
+    let request = test::TestRequest::get()
+        .uri(&format!("/jobs/{}", message_id))
+        .insert_header(bearer("mallory"))
+        .to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_rejects_without_token() {
+    let bus = start_processor().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(crypt_gate::app_state(bus).await)
+            .configure(crypt_gate::configure)
+    ).await;
+
+    let request = test::TestRequest::post()
+        .uri("/encrypt")
+        .set_json(json!("merhaba"))
+        .to_request();
+    let response = test::try_call_service(&app, request).await;
+    let status = match response {
+        Ok(response) => response.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_readiness_checks() {
+    let bus: Arc<dyn MessageBus> = Arc::new(MemoryBus::new());
+    let workers = crypt_processor::start_workers(
+        bus.clone(),
+        Arc::new(CryptService::from_key_material(&CryptService::generate().export_key_material().unwrap()).unwrap()),
+        OperationRegistry::default(),
+        Arc::new(WebSocketManager::new()),
+        WebhookSender::new(WebhookConfig::from_env()),
+    ).await.unwrap();
+    assert!(workers.health().is_ready());
+
+    let app = test::init_service(
+        App::new()
+            .app_data(crypt_gate::app_state(bus).await)
+            .configure(crypt_gate::configure)
+    ).await;
+
+    // Token gerektirmez
+    let request = test::TestRequest::get().uri("/healthz").to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), StatusCode::OK);
+
+    let request = test::TestRequest::get().uri("/readyz").to_request();
+    let ready: Value = test::call_and_read_body_json(&app, request).await;
+    assert_eq!(ready["status"], "ok");
+    assert_eq!(ready["checks"]["message_bus"]["ok"], true);
+    assert_eq!(ready["checks"]["job_results_consumer"]["ok"], true);
+}
+
+async fn start_grpc(bus: Arc<dyn MessageBus>) -> CryptGateClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(crypt_gate::grpc::serve(crypt_gate::app_state(bus).await, listener));
+    CryptGateClient::connect(format!("http://{}", addr)).await.unwrap()
+}
+
+fn grpc_request<T>(message: T, sub: &str) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request.metadata_mut().insert("authorization", bearer(sub).1.parse().unwrap());
+    request
+}
+
+fn sync_options() -> Option<proto::SubmitOptions> {
+    Some(proto::SubmitOptions { mode: proto::SubmitMode::Sync as i32, ..Default::default() })
+}
+
+#[actix_web::test]
+async fn test_grpc_round_trip() {
+    let mut client = start_grpc(start_processor().await).await;
+
+    let request = tonic::Request::new(proto::EncryptRequest { plaintext: "merhaba".to_string(), options: None });
+    assert_eq!(client.encrypt(request).await.unwrap_err().code(), Code::Unauthenticated);
+
+    let request = proto::EncryptRequest { plaintext: "merhaba".to_string(), options: sync_options() };
+    let encrypted = client.encrypt(grpc_request(request, "alice")).await.unwrap().into_inner();
+    assert_eq!(encrypted.status, proto::JobStatus::Done as i32);
+    let encrypted: Value = serde_json::from_str(encrypted.data.as_deref().unwrap()).unwrap();
+
+    // Akışta hatalı öğe akışı kesmez, kendi yanıtında raporlanır
+    let items = vec![
+        proto::DecryptRequest {
+            encrypted: Some(proto::EncryptedData {
+                encrypted_key: encrypted["encrypted_key"].as_str().unwrap().to_string(),
+                nonce: encrypted["nonce"].as_str().unwrap().to_string(),
+                data: encrypted["data"].as_str().unwrap().to_string(),
+            }),
+            options: sync_options(),
+        },
+        proto::DecryptRequest { encrypted: None, options: sync_options() },
+    ];
+    let request = grpc_request(tokio_stream::iter(items), "alice");
+    let mut replies = client.decrypt_stream(request).await.unwrap().into_inner();
+
+    let decrypted = replies.message().await.unwrap().unwrap();
+    assert_eq!(decrypted.status, proto::JobStatus::Done as i32);
+    assert_eq!(decrypted.data.as_deref(), Some("merhaba"));
+    let invalid = replies.message().await.unwrap().unwrap();
+    assert_eq!(invalid.status, proto::JobStatus::Failed as i32);
+    assert_eq!(invalid.code, "VALIDATION_ERROR");
+    assert!(replies.message().await.unwrap().is_none());
+}
+
+#[actix_web::test]
+async fn test_grpc_watch_results() {
+    let mut client = start_grpc(start_processor().await).await;
+
+    let request = grpc_request(proto::WatchResultsRequest {}, "alice");
+    let mut results = client.watch_results(request).await.unwrap().into_inner();
+
+    // Başka kullanıcının sonucu akışa düşmez
+    let request = proto::EncryptRequest { plaintext: "mallory".to_string(), options: None };
+    client.encrypt(grpc_request(request, "mallory")).await.unwrap();
+    let request = proto::EncryptRequest { plaintext: "merhaba".to_string(), options: None };
+    let accepted = client.encrypt(grpc_request(request, "alice")).await.unwrap().into_inner();
+    assert_eq!(accepted.status, proto::JobStatus::Processing as i32);
+
+    // Debug derlemede işlenmesi birkaç saniye sürebilir
+    let job = tokio::time::timeout(Duration::from_secs(30), results.message())
+        .await.unwrap().unwrap().unwrap();
+    assert_eq!(job.message_id, accepted.message_id);
+    assert_eq!(job.status, proto::JobStatus::Done as i32);
+
+    let request = grpc_request(proto::GetJobRequest { message_id: accepted.message_id.clone() }, "mallory");
+    assert_eq!(client.get_job(request).await.unwrap_err().code(), Code::NotFound);
+    let request = grpc_request(proto::GetJobRequest { message_id: accepted.message_id }, "alice");
+    assert_eq!(client.get_job(request).await.unwrap().into_inner().status, proto::JobStatus::Done as i32);
+}
+
+#[actix_web::test]
+async fn test_webhook_delivery() {
+    // Sonucu alan yerel HTTP alıcısı
+    let (sender, mut received) = tokio::sync::mpsc::unbounded_channel::<(String, String)>();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let callback_url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = actix_web::HttpServer::new(move || {
+        let sender = sender.clone();
+        App::new().route("/hook", actix_web::web::post().to(move |req: actix_web::HttpRequest, body: String| {
+            let signature = req.headers().get(crypt_processor::SIGNATURE_HEADER).unwrap().to_str().unwrap().to_string();
+            sender.send((signature, body)).unwrap();
+            async { actix_web::HttpResponse::NoContent().finish() }
+        }))
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    actix_web::rt::spawn(server);
+
+    let bus: Arc<dyn MessageBus> = Arc::new(MemoryBus::new());
+    crypt_processor::start_workers(
+        bus.clone(),
+        Arc::new(CryptService::new()),
+        OperationRegistry::default(),
+        Arc::new(WebSocketManager::new()),
+        WebhookSender::new(WebhookConfig {
+            secrets: HashMap::from([("alice".to_string(), "gizli".to_string())]),
+            ..WebhookConfig::from_env()
+        }),
+    ).await.unwrap();
+    bus.publish(ENCRYPT_QUEUE, &JobMessage {
+        schema_version: SCHEMA_VERSION,
+        id: "job-1".to_string(),
+        operation: Operation::Encrypt,
+        data: "merhaba".to_string(),
+        subject: "alice".to_string(),
+        callback_url: Some(callback_url),
+        traceparent: None,
+    }).await.unwrap();
+
+    let (signature, body) = tokio::time::timeout(Duration::from_secs(30), received.recv())
+        .await.unwrap().unwrap();
+    let timestamp: u64 = signature.strip_prefix("t=").unwrap().split(',').next().unwrap().parse().unwrap();
+    assert_eq!(signature, crypt_processor::sign(b"gizli", timestamp, &body));
+    let result: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(result["message_id"], "job-1");
+    assert_eq!(result["success"], true);
+}
+
+// Yanıt vermeyen alıcıya yapılan tekrar denemeler prefetch sınırını tutmaz
+#[actix_web::test]
+async fn test_slow_webhook_does_not_hold_prefetch() {
+    let (sender, mut attempts) = tokio::sync::mpsc::unbounded_channel::<()>();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let callback_url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = actix_web::HttpServer::new(move || {
+        let sender = sender.clone();
+        App::new().route("/hook", actix_web::web::post().to(move || {
+            sender.send(()).unwrap();
+            async { actix_web::HttpResponse::ServiceUnavailable().finish() }
+        }))
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    actix_web::rt::spawn(server);
+
+    let bus: Arc<dyn MessageBus> = Arc::new(MemoryBus::new().with_prefetch(1));
+    // crypt-gate iş deposunun yerine sonuçları dinler
+    let mut results = bus.subscribe_results().await.unwrap();
+    crypt_processor::start_workers(
+        bus.clone(),
+        Arc::new(CryptService::new()),
+        OperationRegistry::default(),
+        Arc::new(WebSocketManager::new()),
+        WebhookSender::new(WebhookConfig {
+            secrets: HashMap::from([("alice".to_string(), "gizli".to_string())]),
+            max_attempts: 10,
+            initial_backoff: Duration::from_secs(30),
+        }),
+    ).await.unwrap();
+
+    let job = |id: &str, callback_url: Option<String>| JobMessage {
+        schema_version: SCHEMA_VERSION,
+        id: id.to_string(),
+        operation: Operation::Encrypt,
+        data: "merhaba".to_string(),
+        subject: "alice".to_string(),
+        callback_url,
+        traceparent: None,
+    };
+    bus.publish(ENCRYPT_QUEUE, &job("job-1", Some(callback_url))).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(30), attempts.recv()).await.unwrap().unwrap();
+    bus.publish(ENCRYPT_QUEUE, &job("job-2", None)).await.unwrap();
+
+    let mut finished = Vec::new();
+    while finished.len() < 2 {
+        let result = tokio::time::timeout(Duration::from_secs(30), results.recv()).await.unwrap().unwrap();
+        finished.push(result.message_id);
+    }
+    assert_eq!(finished, ["job-1", "job-2"]);
+    assert_eq!(bus.queue_depth(ENCRYPT_QUEUE).await.unwrap(), 0);
+    // job-1 hâlâ ikinci denemeyi bekliyor
+    assert!(attempts.try_recv().is_err());
+}
+
+#[actix_web::test]
+async fn test_event_stream() {
+    let bus: Arc<dyn MessageBus> = Arc::new(MemoryBus::new());
+    let manager = Arc::new(WebSocketManager::new());
+    let webhooks = WebhookSender::new(WebhookConfig::from_env());
+    let delivery_log = actix_web::web::Data::new(webhooks.log().clone());
+    let workers = crypt_processor::start_workers(
+        bus.clone(),
+        Arc::new(CryptService::new()),
+        OperationRegistry::default(),
+        manager.clone(),
+        webhooks,
+    ).await.unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::new(manager))
+            .app_data(actix_web::web::Data::new(workers))
+            .app_data(delivery_log)
+            .configure(crypt_processor::configure)
+    ).await;
+
+    let request = test::TestRequest::get().uri("/events").to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+    // WebSocket de aynı kimlik doğrulamasını ister
+    let request = test::TestRequest::get()
+        .uri("/ws")
+        .insert_header(("Upgrade", "websocket"))
+        .insert_header(("Connection", "Upgrade"))
+        .insert_header(("Sec-WebSocket-Version", "13"))
+        .insert_header(("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+        .to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    // Token adres satırında kabul edilmez; tarayıcı önce oturum çerezi alır
+    let (_, authorization) = bearer("alice");
+    let token = authorization.trim_start_matches("Bearer ");
+    let request = test::TestRequest::get().uri(&format!("/events?access_token={}", token)).to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    let request = test::TestRequest::post().uri("/events/session").insert_header(bearer("alice")).to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let cookie = response.response().cookies().next().unwrap().into_owned();
+    assert_eq!(cookie.name(), crypt_processor::EVENTS_COOKIE);
+    assert_eq!(cookie.http_only(), Some(true));
+    assert_eq!(cookie.secure(), Some(true));
+    assert_eq!(cookie.same_site(), Some(actix_web::cookie::SameSite::Strict));
+    assert_eq!(cookie.path(), Some("/"));
+
+    let request = test::TestRequest::get()
+        .uri("/events")
+        .cookie(actix_web::cookie::Cookie::new(crypt_processor::EVENTS_COOKIE, "bilinmeyen"))
+        .to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+    let request = test::TestRequest::get()
+        .uri("/ws")
+        .cookie(cookie.clone())
+        .insert_header(("Upgrade", "websocket"))
+        .insert_header(("Connection", "Upgrade"))
+        .insert_header(("Sec-WebSocket-Version", "13"))
+        .insert_header(("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ=="))
+        .to_request();
+    assert_eq!(test::call_service(&app, request).await.status(), StatusCode::SWITCHING_PROTOCOLS);
+
+    let request = test::TestRequest::get().uri("/events").cookie(cookie).to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/event-stream");
+    let mut body = Box::pin(response.into_body());
+
+    for (id, subject) in [("job-bob", "bob"), ("job-alice", "alice")] {
+        bus.publish(ENCRYPT_QUEUE, &JobMessage {
+            schema_version: SCHEMA_VERSION,
+            id: id.to_string(),
+            operation: Operation::Encrypt,
+            data: "merhaba".to_string(),
+            subject: subject.to_string(),
+            callback_url: None,
+            traceparent: None,
+        }).await.unwrap();
+    }
+
+    // İlk parça retry satırı, ardından yalnızca alice'in sonucu gelir
+    let mut received = String::new();
+    while !received.contains("job-alice") {
+        let chunk = tokio::time::timeout(
+            Duration::from_secs(30),
+            std::future::poll_fn(|cx| actix_web::body::MessageBody::poll_next(body.as_mut(), cx)),
+        ).await.unwrap().unwrap().unwrap();
+        received.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+    assert!(received.starts_with("retry: "));
+    assert!(received.contains("id: 2\ndata: {\"schema_version\":1,\"success\":true,\"message_id\":\"job-alice\""));
+    assert!(!received.contains("job-bob"));
+}
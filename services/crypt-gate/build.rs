@@ -0,0 +1,9 @@
+// protoc gerektirmemek için .proto dosyası protox ile derlenir
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto/crypt_gate.proto");
+    let descriptors = protox::compile(["proto/crypt_gate.proto"], ["proto"])?;
+    tonic_build::configure()
+        .build_client(true)
+        .compile_fds(descriptors)?;
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use message_bus::{BusError, MessageBus};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+use crate::middleware::ServiceError;
+
+#[derive(Deserialize, Default, Clone, Copy, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Thresholds {
+    pub shed_depth: u32,    // bu derinlikten sonra düşük öncelikli işler reddedilir
+    pub max_depth: u32,     // bu derinlikten sonra tüm işler reddedilir
+    pub retry_after: Duration,
+}
+
+// Kuyruk derinlikleri passive queue_declare ile periyodik olarak okunur; iki okuma
+// arasında yayınlanan işler yerel sayaca eklenir, böylece ani yükler de görülür
+#[derive(Clone)]
+pub struct Backpressure {
+    depths: Arc<Mutex<HashMap<String, u32>>>,
+    thresholds: Thresholds,
+}
+
+impl Backpressure {
+    pub fn new(thresholds: Thresholds) -> Self {
+        Self {
+            depths: Arc::new(Mutex::new(HashMap::new())),
+            thresholds,
+        }
+    }
+
+    pub async fn admit(&self, queue: &str, priority: Priority) -> Result<(), ServiceError> {
+        let depth = self.depths.lock().await.get(queue).copied().unwrap_or(0);
+        let limit = match priority {
+            Priority::Low => self.thresholds.shed_depth,
+            Priority::Normal => self.thresholds.max_depth,
+        };
+
+        if depth < limit {
+            return Ok(());
+        }
+
+        Err(ServiceError::Overloaded(
+            format!("{} kuyruğunda {} iş bekliyor, lütfen daha sonra tekrar deneyin", queue, depth),
+            self.thresholds.retry_after.as_secs().max(1),
+        ))
+    }
+
+    pub async fn note_published(&self, queue: &str) {
+        *self.depths.lock().await.entry(queue.to_string()).or_insert(0) += 1;
+    }
+
+    pub async fn set_depth(&self, queue: &str, depth: u32) {
+        self.depths.lock().await.insert(queue.to_string(), depth);
+    }
+
+    // Bağlantı yokken okuma atlanır, son bilinen derinlik geçerli kalır
+    pub fn watch(&self, bus: Arc<dyn MessageBus>, queues: &[&str], interval: Duration) {
+        let backpressure = self.clone();
+        let queues: Vec<String> = queues.iter().map(|q| q.to_string()).collect();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                for queue in &queues {
+                    match bus.queue_depth(queue).await {
+                        Ok(depth) => backpressure.set_depth(queue, depth).await,
+                        Err(BusError::NotConnected) => {}
+                        Err(e) => tracing::warn!(queue, error = %e, "Kuyruk derinliği okunamadı"),
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backpressure() -> Backpressure {
+        Backpressure::new(Thresholds {
+            shed_depth: 2,
+            max_depth: 4,
+            retry_after: Duration::from_secs(5),
+        })
+    }
+
+    #[actix_web::test]
+    async fn test_sheds_low_priority_first() {
+        let backpressure = backpressure();
+        assert!(backpressure.admit("encrypt_queue", Priority::Low).await.is_ok());
+
+        backpressure.set_depth("encrypt_queue", 2).await;
+        assert!(backpressure.admit("encrypt_queue", Priority::Low).await.is_err());
+        assert!(backpressure.admit("encrypt_queue", Priority::Normal).await.is_ok());
+        assert!(backpressure.admit("decrypt_queue", Priority::Low).await.is_ok());
+
+        backpressure.set_depth("encrypt_queue", 4).await;
+        assert!(matches!(
+            backpressure.admit("encrypt_queue", Priority::Normal).await,
+            Err(ServiceError::Overloaded(_, 5))
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_published_jobs_count_until_next_poll() {
+        let backpressure = backpressure();
+        for _ in 0..4 {
+            backpressure.note_published("encrypt_queue").await;
+        }
+        assert!(backpressure.admit("encrypt_queue", Priority::Normal).await.is_err());
+
+        backpressure.set_depth("encrypt_queue", 0).await;
+        assert!(backpressure.admit("encrypt_queue", Priority::Normal).await.is_ok());
+    }
+}
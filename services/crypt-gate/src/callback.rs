@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use url::Url;
+use crate::middleware::{FieldError, ServiceError};
+
+const MAX_URL_LENGTH: usize = 2048;
+
+// Kullanıcı (JWT sub) başına izin verilen callback adres önekleri (WEBHOOK_ALLOWLIST):
+// {"alice": ["https://hooks.example.com/crypt/"]}
+// Listede olmayan kullanıcı callback_url kullanamaz
+#[derive(Default)]
+pub struct CallbackAllowList {
+    prefixes: HashMap<String, Vec<Url>>,
+}
+
+fn is_web_url(url: &Url) -> bool {
+    matches!(url.scheme(), "http" | "https") && url.host_str().is_some()
+}
+
+// Aynı köken (şema, host, port) ve yol öneki; "/crypt" "/crypt-evil"i kapsamaz
+fn matches_prefix(prefix: &Url, url: &Url) -> bool {
+    if prefix.scheme() != url.scheme()
+        || prefix.host_str() != url.host_str()
+        || prefix.port_or_known_default() != url.port_or_known_default() {
+        return false;
+    }
+
+    let prefix_path = prefix.path().trim_end_matches('/');
+    let path = url.path();
+    path == prefix_path
+        || path.strip_prefix(prefix_path).is_some_and(|rest| rest.starts_with('/'))
+}
+
+impl CallbackAllowList {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let raw: HashMap<String, Vec<String>> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut prefixes = HashMap::new();
+        for (subject, urls) in raw {
+            let urls = urls.iter()
+                .map(|url| Url::parse(url)
+                    .ok()
+                    .filter(is_web_url)
+                    .ok_or_else(|| format!("Geçersiz callback öneki: {}", url)))
+                .collect::<Result<Vec<_>, _>>()?;
+            prefixes.insert(subject, urls);
+        }
+        Ok(Self { prefixes })
+    }
+
+    pub fn check(&self, subject: &str, callback_url: &str) -> Result<(), ServiceError> {
+        let url = Some(callback_url)
+            .filter(|url| url.len() <= MAX_URL_LENGTH)
+            .and_then(|url| Url::parse(url).ok())
+            // Kullanıcı bilgisi içeren adresler imza sırrı dışında kimlik bilgisi taşımasın
+            .filter(|url| is_web_url(url) && url.username().is_empty() && url.password().is_none())
+            .ok_or_else(|| ServiceError::ValidationError(vec![
+                FieldError::new("callback_url", "Geçerli bir http(s) adresi olmalıdır"),
+            ]))?;
+
+        let allowed = self.prefixes.get(subject)
+            .is_some_and(|prefixes| prefixes.iter().any(|prefix| matches_prefix(prefix, &url)));
+        if allowed {
+            Ok(())
+        } else {
+            Err(ServiceError::Forbidden("callback_url bu hesap için izin listesinde değil".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow_list() -> CallbackAllowList {
+        CallbackAllowList::from_json(r#"{"alice": ["https://hooks.example.com/crypt/"]}"#).unwrap()
+    }
+
+    #[test]
+    fn test_allowed_prefix() {
+        let list = allow_list();
+        assert!(list.check("alice", "https://hooks.example.com/crypt").is_ok());
+        assert!(list.check("alice", "https://hooks.example.com/crypt/jobs?id=1").is_ok());
+        assert!(list.check("alice", "https://hooks.example.com:443/crypt/").is_ok());
+    }
+
+    #[test]
+    fn test_rejects_outside_allow_list() {
+        let list = allow_list();
+        for url in [
+            "https://hooks.example.com/crypt-evil",
+            "http://hooks.example.com/crypt/",
+            "https://hooks.example.com:8443/crypt/",
+            "https://hooks.example.com.evil.net/crypt/",
+        ] {
+            assert!(matches!(list.check("alice", url), Err(ServiceError::Forbidden(_))), "{}", url);
+        }
+        assert!(matches!(list.check("bob", "https://hooks.example.com/crypt/"), Err(ServiceError::Forbidden(_))));
+    }
+
+    #[test]
+    fn test_rejects_malformed_url() {
+        let list = allow_list();
+        for url in ["hooks.example.com/crypt", "ftp://hooks.example.com/crypt/", "https://user:pw@hooks.example.com/crypt/"] {
+            assert!(matches!(list.check("alice", url), Err(ServiceError::ValidationError(_))), "{}", url);
+        }
+        assert!(CallbackAllowList::from_json(r#"{"alice": ["/relative"]}"#).is_err());
+    }
+}
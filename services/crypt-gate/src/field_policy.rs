@@ -0,0 +1,80 @@
+use backend::field::{authorized_paths, JsonPath};
+use jwt_validator::Claims;
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::middleware::ServiceError;
+
+// Alan çözme yetkisi (FIELD_DECRYPT_POLICY): kullanıcı (JWT sub) ve tier başına
+// çözülebilecek yol kalıpları. Bir kullanıcının yetkisi ikisinin birleşimidir:
+// {"subjects": {"alice": ["$.cards[*].pan"]}, "tiers": {"admin": ["$.customer.*"]}}
+// Politikada olmayan kullanıcı hiçbir alanı çözemez
+#[derive(Deserialize, Default)]
+pub struct FieldDecryptPolicy {
+    #[serde(default)]
+    subjects: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    tiers: HashMap<String, Vec<String>>,
+}
+
+impl FieldDecryptPolicy {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let policy: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        for path in policy.subjects.values().chain(policy.tiers.values()).flatten() {
+            JsonPath::parse(path).map_err(|e| e.to_string())?;
+        }
+        Ok(policy)
+    }
+
+    fn allowed(&self, claims: &Claims) -> Vec<String> {
+        let subject = self.subjects.get(&claims.sub).into_iter().flatten();
+        let tier = self.tiers.get(&claims.tier).into_iter().flatten();
+        subject.chain(tier).cloned().collect()
+    }
+
+    // İstenen yolların politikayla kesişimi; processor'a yalnızca bu yollar gider.
+    // İstenen yolların hiçbirine yetki yoksa iş kuyruğa yazılmaz
+    pub fn authorize(&self, claims: &Claims, requested: &[String]) -> Result<Vec<String>, ServiceError> {
+        let paths = authorized_paths(requested, &self.allowed(claims))
+            .map_err(|e| ServiceError::BadRequest(e.to_string()))?;
+        if paths.is_empty() {
+            return Err(ServiceError::Forbidden("İstenen alanları çözme yetkisi yok".to_string()));
+        }
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(sub: &str, tier: &str) -> Claims {
+        Claims { sub: sub.to_string(), iat: 0, exp: 0, tier: tier.to_string() }
+    }
+
+    fn paths(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_subject_and_tier_paths() {
+        let policy = FieldDecryptPolicy::from_json(
+            r#"{"subjects": {"alice": ["$.cards[*].pan"]}, "tiers": {"admin": ["$.customer.*"]}}"#
+        ).unwrap();
+        let requested = paths(&["$.cards[0].pan", "$.customer.ssn"]);
+
+        assert_eq!(policy.authorize(&claims("alice", "free"), &requested).unwrap(), paths(&["$.cards[0].pan"]));
+        assert_eq!(policy.authorize(&claims("bob", "admin"), &requested).unwrap(), paths(&["$.customer.ssn"]));
+        assert!(matches!(policy.authorize(&claims("bob", "free"), &requested), Err(ServiceError::Forbidden(_))));
+        assert!(matches!(
+            FieldDecryptPolicy::default().authorize(&claims("alice", "admin"), &requested),
+            Err(ServiceError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_policy() {
+        assert!(FieldDecryptPolicy::from_json(r#"{"subjects": {"alice": ["cards.pan"]}}"#).is_err());
+        assert!(FieldDecryptPolicy::from_json(r#"{"subjects": ["$.a"]}"#).is_err());
+    }
+}
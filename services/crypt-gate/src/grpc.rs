@@ -0,0 +1,432 @@
+// tonic trait ve interceptor imzaları Result<_, Status> dönmeyi gerektirir
+#![allow(clippy::result_large_err)]
+
+use std::pin::Pin;
+use actix_web::web;
+use backend::crypt::EncryptedData;
+use jwt_validator::{bearer_token, validate_token, Claims};
+use message_bus::Operation;
+use telemetry::{TraceContext, TRACEPARENT_HEADER};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, ReceiverStream, TcpListenerStream};
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::{MetadataMap, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Server;
+use tonic::{Code, Request, Response, Status, Streaming};
+use tracing::Instrument;
+use crate::backpressure::Priority;
+use crate::idempotency::IdempotencyKey;
+use crate::jobs::{JobRecord, JobStatus};
+use crate::middleware::{FieldError, ServiceError};
+use crate::validation::Validate;
+use crate::{max_payload_bytes, submit_job, to_job_data, AppState, SubmitMode, SubmitOptions, Submitted};
+
+pub mod proto {
+    tonic::include_proto!("cryptgate.v1");
+}
+
+use proto::crypt_gate_server::{CryptGate, CryptGateServer};
+
+const IDEMPOTENCY_METADATA: &str = "idempotency-key";
+const ERROR_CODE_METADATA: &str = "x-error-code";
+// Akışta işlenmeyi bekleyen en fazla yanıt; istemci okumazsa gönderim yavaşlar
+const STREAM_BUFFER: usize = 32;
+
+type ReplyStream = ReceiverStream<Result<proto::SubmitReply, Status>>;
+type JobStream = Pin<Box<dyn Stream<Item = Result<proto::Job, Status>> + Send>>;
+
+// REST ile aynı hata sınıfları; ErrorBody.code x-error-code metadata'sında döner
+impl From<ServiceError> for Status {
+    fn from(error: ServiceError) -> Self {
+        let code = match &error {
+            ServiceError::BadRequest(_)
+            | ServiceError::ValidationError(_)
+            | ServiceError::UnsupportedMediaType(_) => Code::InvalidArgument,
+            ServiceError::Unauthorized(_) => Code::Unauthenticated,
+            ServiceError::Forbidden(_) => Code::PermissionDenied,
+            ServiceError::NotFound(_) => Code::NotFound,
+            ServiceError::Conflict(_) => Code::FailedPrecondition,
+            ServiceError::RateLimited(..) | ServiceError::PayloadTooLarge(_) => Code::ResourceExhausted,
+            ServiceError::QueueError(_) | ServiceError::Overloaded(..) => Code::Unavailable,
+            ServiceError::Timeout(_) => Code::DeadlineExceeded,
+            ServiceError::EncryptionError(_)
+            | ServiceError::DecryptionError(_)
+            | ServiceError::ServiceLockError(_)
+            | ServiceError::SerializationError(_) => Code::Internal,
+        };
+
+        let mut status = Status::new(code, describe(&error));
+        let metadata = status.metadata_mut();
+        metadata.insert(ERROR_CODE_METADATA, MetadataValue::from_static(error.code()));
+        let retry_after = match &error {
+            ServiceError::Overloaded(_, retry_after) => Some(*retry_after),
+            ServiceError::RateLimited(_, limit) => Some(limit.reset.max(1)),
+            _ => None,
+        };
+        if let Some(retry_after) = retry_after {
+            metadata.insert("retry-after", MetadataValue::from(retry_after));
+        }
+        status
+    }
+}
+
+// Alan hataları tek satırda: "İstek doğrulanamadı (body: Şifrelenecek veri boş olamaz)"
+fn describe(error: &ServiceError) -> String {
+    let details: Vec<String> = error.details().iter()
+        .map(|detail| format!("{}: {}", detail.field, detail.message))
+        .collect();
+
+    if details.is_empty() {
+        error.message()
+    } else {
+        format!("{} ({})", error.message(), details.join("; "))
+    }
+}
+
+fn proto_status(status: JobStatus) -> i32 {
+    match status {
+        JobStatus::Processing => proto::JobStatus::Processing as i32,
+        JobStatus::Done => proto::JobStatus::Done as i32,
+        JobStatus::Failed => proto::JobStatus::Failed as i32,
+        JobStatus::Expired => proto::JobStatus::Expired as i32,
+    }
+}
+
+fn proto_job(job: JobRecord) -> proto::Job {
+    proto::Job {
+        message_id: job.message_id,
+        status: proto_status(job.status),
+        result: job.result,
+        error: job.error,
+    }
+}
+
+fn submit_reply(submitted: Submitted) -> proto::SubmitReply {
+    match submitted {
+        Submitted::Accepted(message_id) => proto::SubmitReply {
+            message_id,
+            status: proto_status(JobStatus::Processing),
+            ..Default::default()
+        },
+        Submitted::Replied(reply) => proto::SubmitReply {
+            message_id: reply.message_id,
+            status: proto_status(if reply.success { JobStatus::Done } else { JobStatus::Failed }),
+            data: reply.data,
+            error: reply.error,
+            code: reply.code.map(|code| code.to_string()).unwrap_or_default(),
+            ..Default::default()
+        },
+        Submitted::Replayed { message_id, job } => {
+            let job = job.map(proto_job).unwrap_or_else(|| proto::Job {
+                message_id,
+                status: proto_status(JobStatus::Processing),
+                ..Default::default()
+            });
+            proto::SubmitReply {
+                message_id: job.message_id,
+                status: job.status,
+                data: job.result,
+                error: job.error,
+                replayed: true,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+// Akıştaki bir öğenin gateway hatası; akış kesilmez
+fn failed_reply(error: &ServiceError) -> proto::SubmitReply {
+    proto::SubmitReply {
+        status: proto_status(JobStatus::Failed),
+        error: Some(describe(error)),
+        code: error.code().to_string(),
+        ..Default::default()
+    }
+}
+
+fn submit_options(options: &proto::SubmitOptions) -> Result<SubmitOptions, ServiceError> {
+    let mode = match proto::SubmitMode::try_from(options.mode) {
+        Ok(proto::SubmitMode::Async) => SubmitMode::Async,
+        Ok(proto::SubmitMode::Sync) => SubmitMode::Sync,
+        Err(_) => return Err(ServiceError::ValidationError(vec![FieldError::new("options.mode", "Bilinmeyen değer")])),
+    };
+    let priority = match proto::Priority::try_from(options.priority) {
+        Ok(proto::Priority::Normal) => Priority::Normal,
+        Ok(proto::Priority::Low) => Priority::Low,
+        Err(_) => return Err(ServiceError::ValidationError(vec![FieldError::new("options.priority", "Bilinmeyen değer")])),
+    };
+    Ok(SubmitOptions { mode, priority, callback_url: options.callback_url.clone() })
+}
+
+// Doğrulanmış, kuyruğa yazılmaya hazır istek
+struct Submission {
+    operation: Operation,
+    data: String,
+    options: proto::SubmitOptions,
+}
+
+impl TryFrom<proto::EncryptRequest> for Submission {
+    type Error = ServiceError;
+
+    fn try_from(request: proto::EncryptRequest) -> Result<Self, Self::Error> {
+        request.plaintext.validated()?;
+        Ok(Submission {
+            operation: Operation::Encrypt,
+            data: request.plaintext,
+            options: request.options.unwrap_or_default(),
+        })
+    }
+}
+
+impl TryFrom<proto::DecryptRequest> for Submission {
+    type Error = ServiceError;
+
+    fn try_from(request: proto::DecryptRequest) -> Result<Self, Self::Error> {
+        let encrypted = request.encrypted
+            .map(|encrypted| EncryptedData {
+                encrypted_key: encrypted.encrypted_key,
+                nonce: encrypted.nonce,
+                data: encrypted.data,
+            })
+            .ok_or_else(|| ServiceError::ValidationError(vec![FieldError::new("encrypted", "Zorunlu alan")]))?;
+        encrypted.validated()?;
+
+        Ok(Submission {
+            operation: Operation::Decrypt,
+            data: to_job_data(&encrypted)?,
+            options: request.options.unwrap_or_default(),
+        })
+    }
+}
+
+// Çağrının kimliği ve metadata'sı; claims interceptor tarafından eklenir
+#[derive(Clone)]
+struct Call {
+    claims: Claims,
+    trace: TraceContext,
+    idempotency_key: Option<String>,
+}
+
+fn metadata_str<'a>(metadata: &'a MetadataMap, key: &str) -> Option<&'a str> {
+    metadata.get(key).and_then(|value| value.to_str().ok())
+}
+
+impl Call {
+    fn new<T>(request: &Request<T>) -> Result<Self, Status> {
+        let claims = request.extensions().get::<Claims>().cloned()
+            .ok_or_else(|| ServiceError::Unauthorized("JWT yapılandırması bulunamadı".to_string()))?;
+        let metadata = request.metadata();
+        let idempotency_key = match metadata.get(IDEMPOTENCY_METADATA) {
+            Some(value) => IdempotencyKey::parse(value.to_str().ok())?.0,
+            None => None,
+        };
+
+        Ok(Call {
+            claims,
+            trace: TraceContext::continue_from(metadata_str(metadata, TRACEPARENT_HEADER)),
+            idempotency_key,
+        })
+    }
+
+    // REST'teki rate_limit middleware'i + handler ile aynı adımlar
+    async fn submit(&self, state: &AppState, submission: Submission) -> Result<proto::SubmitReply, ServiceError> {
+        state.rate_limiter.check_rate(&format!("sub:{}", self.claims.sub), &self.claims.tier).await?;
+        let options = submit_options(&submission.options)?;
+        let idempotency_key = match submission.options.idempotency_key.as_deref() {
+            Some(key) => IdempotencyKey::parse(Some(key))?,
+            None => IdempotencyKey(self.idempotency_key.clone()),
+        };
+
+        let span = tracing::info_span!(
+            "grpc_request",
+            trace_id = %self.trace.trace_id,
+            span_id = %self.trace.span_id,
+            operation = %submission.operation,
+        );
+        submit_job(
+            state, &self.claims, &options, &idempotency_key, &self.trace,
+            submission.operation, submission.data,
+        )
+        .instrument(span)
+        .await
+        .map(submit_reply)
+    }
+}
+
+struct GateService {
+    state: web::Data<AppState>,
+}
+
+impl GateService {
+    async fn submit_one<T>(&self, request: Request<T>) -> Result<Response<proto::SubmitReply>, Status>
+    where
+        Submission: TryFrom<T, Error = ServiceError>,
+    {
+        let call = Call::new(&request)?;
+        let submission = Submission::try_from(request.into_inner())?;
+        Ok(Response::new(call.submit(&self.state, submission).await?))
+    }
+
+    // Öğeler sırayla işlenir ve yanıtlar aynı sırayla döner. Metadata'daki
+    // idempotency-key akışa uygulanmaz; öğe başına options.idempotency_key kullanılır
+    fn submit_stream<T>(&self, request: Request<Streaming<T>>) -> Result<Response<ReplyStream>, Status>
+    where
+        T: Send + 'static,
+        Submission: TryFrom<T, Error = ServiceError>,
+    {
+        let call = Call { idempotency_key: None, ..Call::new(&request)? };
+        let mut inbound = request.into_inner();
+        let state = self.state.clone();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            loop {
+                let item = match inbound.message().await {
+                    Ok(Some(item)) => item,
+                    Ok(None) => break,
+                    Err(status) => {
+                        let _ = sender.send(Err(status)).await;
+                        break;
+                    }
+                };
+                let reply = match Submission::try_from(item) {
+                    Ok(submission) => call.submit(&state, submission).await,
+                    Err(e) => Err(e),
+                };
+                // İstemci ayrıldıysa kalan öğeler işlenmez
+                if sender.send(Ok(reply.unwrap_or_else(|e| failed_reply(&e)))).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+#[tonic::async_trait]
+impl CryptGate for GateService {
+    type EncryptStreamStream = ReplyStream;
+    type DecryptStreamStream = ReplyStream;
+    type WatchResultsStream = JobStream;
+
+    async fn encrypt(&self, request: Request<proto::EncryptRequest>) -> Result<Response<proto::SubmitReply>, Status> {
+        self.submit_one(request).await
+    }
+
+    async fn decrypt(&self, request: Request<proto::DecryptRequest>) -> Result<Response<proto::SubmitReply>, Status> {
+        self.submit_one(request).await
+    }
+
+    async fn encrypt_stream(
+        &self,
+        request: Request<Streaming<proto::EncryptRequest>>,
+    ) -> Result<Response<ReplyStream>, Status> {
+        self.submit_stream(request)
+    }
+
+    async fn decrypt_stream(
+        &self,
+        request: Request<Streaming<proto::DecryptRequest>>,
+    ) -> Result<Response<ReplyStream>, Status> {
+        self.submit_stream(request)
+    }
+
+    async fn get_job(&self, request: Request<proto::GetJobRequest>) -> Result<Response<proto::Job>, Status> {
+        let call = Call::new(&request)?;
+        let message_id = request.into_inner().message_id;
+        let job = self.state.jobs.get(&message_id, &call.claims.sub).await
+            .ok_or_else(|| ServiceError::NotFound(format!("İş bulunamadı: {}", message_id)))?;
+
+        Ok(Response::new(proto_job(job)))
+    }
+
+    // Yalnızca abone olduktan sonra biten işler gelir; öncekiler GetJob ile sorgulanır
+    async fn watch_results(&self, request: Request<proto::WatchResultsRequest>) -> Result<Response<JobStream>, Status> {
+        let subject = Call::new(&request)?.claims.sub;
+        let finished = BroadcastStream::new(self.state.jobs.watch());
+
+        let jobs = finished.filter_map(move |job| match job {
+            Ok(job) if job.subject == subject => Some(Ok(proto_job(job))),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(Err(Status::data_loss(format!(
+                "{} sonuç kaçırıldı; eksik işleri GetJob ile sorgulayın", skipped
+            )))),
+        });
+        Ok(Response::new(Box::pin(jobs)))
+    }
+}
+
+// REST'teki jwt_auth ile aynı token doğrulaması
+fn authenticate(secret: &str, mut request: Request<()>) -> Result<Request<()>, Status> {
+    let header_value = metadata_str(request.metadata(), "authorization").unwrap_or_default();
+    let claims = bearer_token(header_value)
+        .and_then(|token| validate_token(token, secret.as_bytes()))
+        .map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+
+    request.extensions_mut().insert(claims);
+    Ok(request)
+}
+
+// REST ile aynı AppState'i paylaşır; kuyruk, kota, backpressure ve idempotency ortaktır
+pub async fn serve(state: web::Data<AppState>, listener: TcpListener) -> Result<(), tonic::transport::Error> {
+    let secret = state.jwt_secret.clone();
+    let service = CryptGateServer::new(GateService { state })
+        .max_decoding_message_size(max_payload_bytes());
+    let service = InterceptedService::new(service, move |request| authenticate(&secret, request));
+
+    Server::builder()
+        .add_service(service)
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ratelimit::RateLimitStatus;
+
+    #[test]
+    fn test_service_error_to_status() {
+        let status = Status::from(ServiceError::ValidationError(vec![FieldError::new("body", "Boş olamaz")]));
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "İstek doğrulanamadı (body: Boş olamaz)");
+        assert_eq!(status.metadata().get(ERROR_CODE_METADATA).unwrap(), "VALIDATION_ERROR");
+
+        let status = Status::from(ServiceError::Overloaded("kuyruk dolu".to_string(), 5));
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(status.metadata().get("retry-after").unwrap(), "5");
+
+        let limit = RateLimitStatus { limit: 10, remaining: 0, reset: 0 };
+        let status = Status::from(ServiceError::RateLimited("sınır".to_string(), limit));
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.metadata().get("retry-after").unwrap(), "1");
+    }
+
+    #[test]
+    fn test_submission_is_validated() {
+        let request = proto::EncryptRequest { plaintext: String::new(), options: None };
+        assert!(matches!(Submission::try_from(request), Err(ServiceError::ValidationError(_))));
+
+        let request = proto::DecryptRequest { encrypted: None, options: None };
+        match Submission::try_from(request) {
+            Err(ServiceError::ValidationError(details)) => assert_eq!(details[0].field, "encrypted"),
+            _ => panic!("encrypted zorunlu olmalıydı"),
+        }
+
+        let options = proto::SubmitOptions { mode: 7, ..Default::default() };
+        assert!(submit_options(&options).is_err());
+    }
+
+    #[test]
+    fn test_authenticate_requires_bearer_token() {
+        let status = authenticate("gizli", Request::new(())).unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+
+        let mut request = Request::new(());
+        request.metadata_mut().insert("authorization", MetadataValue::from_static("Bearer bozuk"));
+        assert_eq!(authenticate("gizli", request).unwrap_err().code(), Code::Unauthenticated);
+    }
+}
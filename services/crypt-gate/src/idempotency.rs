@@ -0,0 +1,193 @@
+use actix_web::{dev::Payload, FromRequest, HttpRequest};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::{ObjectBuilder, Required, Type};
+use utoipa::IntoParams;
+use crate::middleware::ServiceError;
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+const MAX_KEY_LENGTH: usize = 255;
+
+// İsteğe bağlı "Idempotency-Key" başlığı
+pub struct IdempotencyKey(pub Option<String>);
+
+impl FromRequest for IdempotencyKey {
+    type Error = ServiceError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let Some(value) = req.headers().get(IDEMPOTENCY_HEADER) else {
+            return ready(Ok(IdempotencyKey(None)));
+        };
+
+        ready(IdempotencyKey::parse(value.to_str().ok()))
+    }
+}
+
+impl IdempotencyKey {
+    // None: değer görünür ASCII değil (gRPC metadata'sı da aynı kurala tabidir)
+    pub fn parse(value: Option<&str>) -> Result<Self, ServiceError> {
+        value
+            .map(str::trim)
+            .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+            .map(|key| IdempotencyKey(Some(key.to_string())))
+            .ok_or_else(|| ServiceError::BadRequest(format!(
+                "{} 1-{} karakterlik görünür ASCII olmalıdır", IDEMPOTENCY_HEADER, MAX_KEY_LENGTH
+            )))
+    }
+}
+
+impl IntoParams for IdempotencyKey {
+    fn into_params(_: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![ParameterBuilder::new()
+            .name(IDEMPOTENCY_HEADER)
+            .parameter_in(ParameterIn::Header)
+            .required(Required::False)
+            .description(Some(format!(
+                "Aynı anahtar ve gövde ile tekrarlanan istek ilk işi döndürür (1-{} karakter)", MAX_KEY_LENGTH
+            )))
+            .schema(Some(ObjectBuilder::new().schema_type(Type::String).max_length(Some(MAX_KEY_LENGTH))))
+            .build()]
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IdempotencyOutcome {
+    New,
+    Replay(String, Duration),   // ilk isteğin message_id'si ve anahtarın yaşı
+    Conflict,           // aynı anahtar, farklı gövde
+}
+
+struct Entry {
+    fingerprint: [u8; 32],
+    message_id: String,
+    created_at: Instant,
+}
+
+// Anahtarlar kullanıcı bazında tutulur; farklı kullanıcıların aynı anahtarı çakışmaz
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    entries: Arc<Mutex<HashMap<(String, String), Entry>>>,
+    window: Duration,
+}
+
+pub fn fingerprint(operation: &str, mode: &str, callback_url: Option<&str>, data: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    // Alanlar uzunluk önekiyle yazılır; callback_url'in yokluğu boş adresten ayrılır
+    for field in [Some(operation), Some(mode), callback_url] {
+        match field {
+            Some(value) => {
+                hasher.update([1]);
+                hasher.update((value.len() as u64).to_be_bytes());
+                hasher.update(value.as_bytes());
+            }
+            None => hasher.update([0]),
+        }
+    }
+    hasher.update(data.as_bytes());
+    hasher.finalize().into()
+}
+
+impl IdempotencyStore {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            window,
+        }
+    }
+
+    // Anahtar yoksa message_id ile ayrılır; kontrol ve ayırma tek kilit altında yapılır
+    pub async fn begin(&self, subject: &str, key: &str, fingerprint: [u8; 32], message_id: &str) -> IdempotencyOutcome {
+        let mut entries = self.entries.lock().await;
+        let entry_key = (subject.to_string(), key.to_string());
+
+        if let Some(entry) = entries.get(&entry_key) {
+            if entry.created_at.elapsed() < self.window {
+                return if entry.fingerprint == fingerprint {
+                    IdempotencyOutcome::Replay(entry.message_id.clone(), entry.created_at.elapsed())
+                } else {
+                    IdempotencyOutcome::Conflict
+                };
+            }
+        }
+
+        entries.insert(entry_key, Entry {
+            fingerprint,
+            message_id: message_id.to_string(),
+            created_at: Instant::now(),
+        });
+        IdempotencyOutcome::New
+    }
+
+    // Kuyruğa yazılamayan isteğin anahtarı serbest bırakılır, istemci yeniden deneyebilir
+    pub async fn release(&self, subject: &str, key: &str) {
+        self.entries.lock().await.remove(&(subject.to_string(), key.to_string()));
+    }
+
+    pub async fn purge_expired(&self) {
+        let window = self.window;
+        self.entries.lock().await.retain(|_, entry| entry.created_at.elapsed() < window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_replay_and_conflict() {
+        let store = IdempotencyStore::new(Duration::from_secs(60));
+        let body = fingerprint("encrypt", "async", None, "\"merhaba\"");
+
+        assert_eq!(store.begin("admin", "key-1", body, "job-1").await, IdempotencyOutcome::New);
+        assert!(matches!(
+            store.begin("admin", "key-1", body, "job-2").await,
+            IdempotencyOutcome::Replay(id, _) if id == "job-1"
+        ));
+        assert_eq!(
+            store.begin("admin", "key-1", fingerprint("encrypt", "async", None, "\"başka\""), "job-3").await,
+            IdempotencyOutcome::Conflict
+        );
+        assert_eq!(
+            store.begin("admin", "key-1", fingerprint("decrypt", "async", None, "\"merhaba\""), "job-4").await,
+            IdempotencyOutcome::Conflict
+        );
+        assert_eq!(
+            store.begin("admin", "key-1", fingerprint("encrypt", "sync", None, "\"merhaba\""), "job-5").await,
+            IdempotencyOutcome::Conflict
+        );
+        assert_eq!(
+            store.begin("admin", "key-1", fingerprint("encrypt", "async", Some("https://a.example/hook"), "\"merhaba\""), "job-6").await,
+            IdempotencyOutcome::Conflict
+        );
+        assert_ne!(
+            fingerprint("encrypt", "async", Some(""), "\"merhaba\""),
+            body
+        );
+
+        // Anahtarlar kullanıcıya özeldir
+        assert_eq!(store.begin("other", "key-1", body, "job-7").await, IdempotencyOutcome::New);
+    }
+
+    #[actix_web::test]
+    async fn test_release_and_window() {
+        let store = IdempotencyStore::new(Duration::from_millis(10));
+        let body = fingerprint("encrypt", "async", None, "\"merhaba\"");
+
+        store.begin("admin", "key-1", body, "job-1").await;
+        store.release("admin", "key-1").await;
+        assert_eq!(store.begin("admin", "key-1", body, "job-2").await, IdempotencyOutcome::New);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(store.begin("admin", "key-1", body, "job-3").await, IdempotencyOutcome::New);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        store.purge_expired().await;
+        assert!(store.entries.lock().await.is_empty());
+    }
+}
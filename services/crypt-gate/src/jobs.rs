@@ -0,0 +1,239 @@
+use message_bus::{BusError, JobResult, MessageBus};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Processing,
+    Done,
+    Failed,
+    // Idempotency-Key tekrarında ilk işin kaydı JOB_RETENTION_SECS'i aşmışsa
+    Expired,
+}
+
+#[derive(Serialize, Clone, Debug, ToSchema)]
+pub struct JobRecord {
+    pub message_id: String,
+    pub status: JobStatus,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub subject: String,
+    #[serde(skip)]
+    updated_at: Instant,
+}
+
+impl JobRecord {
+    // Sonucu artık saklanmayan iş; yalnızca tekrarlanan isteklere döner
+    pub fn expired(message_id: &str, subject: &str) -> Self {
+        Self {
+            message_id: message_id.to_string(),
+            status: JobStatus::Expired,
+            result: None,
+            error: Some("İş kaydının saklama süresi dolmuş; sonuç artık alınamaz".to_string()),
+            subject: subject.to_string(),
+            updated_at: Instant::now(),
+        }
+    }
+}
+
+// Async modda 202 gövdesi
+#[derive(Serialize, ToSchema)]
+pub struct JobAccepted {
+    pub message_id: String,
+    pub status: JobStatus,
+}
+
+impl JobAccepted {
+    pub fn new(message_id: &str) -> Self {
+        Self { message_id: message_id.to_string(), status: JobStatus::Processing }
+    }
+}
+
+// Yavaş izleyici bu kadar sonuç geride kalırsa eski sonuçları kaçırır
+const WATCH_CAPACITY: usize = 1024;
+
+#[derive(Clone)]
+pub struct JobStore {
+    jobs: Arc<Mutex<HashMap<String, JobRecord>>>,
+    retention: Duration,
+    consuming: Arc<AtomicBool>,
+    finished: broadcast::Sender<JobRecord>,
+}
+
+impl JobStore {
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            retention,
+            consuming: Arc::new(AtomicBool::new(false)),
+            finished: broadcast::channel(WATCH_CAPACITY).0,
+        }
+    }
+
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    pub async fn insert_processing(&self, message_id: &str, subject: &str) {
+        self.jobs.lock().await.insert(message_id.to_string(), JobRecord {
+            message_id: message_id.to_string(),
+            status: JobStatus::Processing,
+            result: None,
+            error: None,
+            subject: subject.to_string(),
+            updated_at: Instant::now(),
+        });
+    }
+
+    pub async fn complete(&self, result: JobResult) {
+        let status = if result.success { JobStatus::Done } else { JobStatus::Failed };
+
+        let record = JobRecord {
+            message_id: result.message_id,
+            status,
+            result: result.data,
+            error: result.error,
+            subject: result.subject,
+            updated_at: Instant::now(),
+        };
+        self.jobs.lock().await.insert(record.message_id.clone(), record.clone());
+        // İzleyici yoksa hata döner; sonuç yine de saklanmıştır
+        let _ = self.finished.send(record);
+    }
+
+    // Biten tüm işler; çağıran kullanıcıya göre süzmelidir
+    pub fn watch(&self) -> broadcast::Receiver<JobRecord> {
+        self.finished.subscribe()
+    }
+
+    // Yalnızca işi gönderen kullanıcı okuyabilir; başkasının işi yokmuş gibi davranılır
+    pub async fn get(&self, message_id: &str, subject: &str) -> Option<JobRecord> {
+        let jobs = self.jobs.lock().await;
+        jobs.get(message_id)
+            .filter(|job| job.subject == subject && job.updated_at.elapsed() < self.retention)
+            .cloned()
+    }
+
+    pub async fn purge_expired(&self) {
+        let retention = self.retention;
+        self.jobs.lock().await.retain(|_, job| job.updated_at.elapsed() < retention);
+    }
+
+    pub async fn consume_results(&self, bus: &dyn MessageBus) -> Result<(), BusError> {
+        let mut results = bus.subscribe_results().await?;
+
+        let store = self.clone();
+        self.consuming.store(true, Ordering::Relaxed);
+        tokio::spawn(async move {
+            while let Some(result) = results.recv().await {
+                store.complete(result).await;
+            }
+            store.consuming.store(false, Ordering::Relaxed);
+            tracing::error!("İş sonucu tüketicisi durdu");
+        });
+
+        Ok(())
+    }
+
+    // Sonuç tüketicisi çalışmıyorsa async işler processing durumunda kalır
+    pub fn is_consuming(&self) -> bool {
+        self.consuming.load(Ordering::Relaxed)
+    }
+
+    pub fn spawn_purge(&self) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                store.purge_expired().await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use message_bus::{ErrorCode, SCHEMA_VERSION};
+
+    fn result(message_id: &str, subject: &str, success: bool) -> JobResult {
+        JobResult {
+            schema_version: SCHEMA_VERSION,
+            message_id: message_id.to_string(),
+            subject: subject.to_string(),
+            success,
+            data: success.then(|| "sonuç".to_string()),
+            error: (!success).then(|| "hata".to_string()),
+            code: (!success).then_some(ErrorCode::CryptError),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_job_lifecycle() {
+        let store = JobStore::new(Duration::from_secs(60));
+
+        store.insert_processing("job-1", "admin").await;
+        assert_eq!(store.get("job-1", "admin").await.unwrap().status, JobStatus::Processing);
+
+        store.complete(result("job-1", "admin", true)).await;
+        let job = store.get("job-1", "admin").await.unwrap();
+        assert_eq!(job.status, JobStatus::Done);
+        assert_eq!(job.result.as_deref(), Some("sonuç"));
+
+        store.complete(result("job-2", "admin", false)).await;
+        assert_eq!(store.get("job-2", "admin").await.unwrap().status, JobStatus::Failed);
+    }
+
+    #[actix_web::test]
+    async fn test_watch_receives_finished_jobs() {
+        let store = JobStore::new(Duration::from_secs(60));
+        let mut finished = store.watch();
+
+        store.insert_processing("job-1", "admin").await;
+        store.complete(result("job-1", "admin", false)).await;
+
+        let job = finished.recv().await.unwrap();
+        assert_eq!(job.message_id, "job-1");
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.subject, "admin");
+    }
+
+    #[actix_web::test]
+    async fn test_only_owner_can_read() {
+        let store = JobStore::new(Duration::from_secs(60));
+        store.insert_processing("job-1", "admin").await;
+
+        assert!(store.get("job-1", "mallory").await.is_none());
+        assert!(store.get("missing", "admin").await.is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_retention() {
+        let store = JobStore::new(Duration::from_millis(10));
+        store.complete(result("job-1", "admin", true)).await;
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(store.get("job-1", "admin").await.is_none());
+
+        store.purge_expired().await;
+        assert!(store.jobs.lock().await.is_empty());
+    }
+
+    #[test]
+    fn test_expired_record() {
+        let json = serde_json::to_value(JobRecord::expired("job-1", "admin")).unwrap();
+
+        assert_eq!(json["status"], "expired");
+        assert!(json["result"].is_null());
+        assert!(json["error"].is_string());
+        assert!(json.get("subject").is_none());
+    }
+}
@@ -0,0 +1,596 @@
+mod backpressure;
+mod callback;
+mod field_policy;
+pub mod grpc;
+mod idempotency;
+mod jobs;
+mod middleware;
+mod openapi;
+mod ratelimit;
+mod validation;
+
+use actix_web::{get, http, middleware::from_fn, post, web, App, HttpResponse, HttpServer};
+use backend::crypt::EncryptedData;
+use backend::field::FieldCryptRequest;
+use backend::fpe::TokenizeRequest;
+use backend::legacy::LegacyEncryptedData;
+use health::HealthReport;
+use jwt_validator::{jwt_secret, Claims};
+use service_metrics::{track_requests, JOBS_PUBLISHED};
+use telemetry::{trace_requests, TraceContext};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use middleware::{json_error_handler, jwt_auth, query_error_handler, rate_limit, ErrorBody, ServiceError};
+use ratelimit::{default_tiers, RateLimiter};
+use validation::Validate;
+use backpressure::{Backpressure, Priority, Thresholds};
+use callback::CallbackAllowList;
+use field_policy::FieldDecryptPolicy;
+use jobs::{JobAccepted, JobRecord, JobStatus, JobStore};
+use idempotency::{fingerprint, IdempotencyKey, IdempotencyOutcome, IdempotencyStore};
+use message_bus::{work_queue, BusError, JobMessage, MessageBus, Operation, SCHEMA_VERSION, WORK_QUEUES};
+use protocol::JobReply;
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+use openapi::{ApiDoc, SubmitResponses};
+
+#[derive(Deserialize, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum SubmitMode {
+    #[default]
+    Async,
+    Sync,
+}
+
+impl SubmitMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SubmitMode::Async => "async",
+            SubmitMode::Sync => "sync",
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SubmitOptions {
+    // async (varsayılan): 202 ve message_id; sync: sonuç beklenir
+    #[serde(default)]
+    mode: SubmitMode,
+    // low: kuyruk yoğunken ilk reddedilen
+    #[serde(default)]
+    priority: Priority,
+    // Sonuç bu adrese POST edilir; WEBHOOK_ALLOWLIST'te hesaba izinli olmalıdır
+    #[param(max_length = 2048)]
+    callback_url: Option<String>,
+}
+
+// Taşıyıcıdan (REST, gRPC) bağımsız gönderim sonucu
+enum Submitted {
+    Accepted(String),       // message_id; sonuç sonradan gelir
+    Replied(JobReply),      // sync modda crypt-processor'ın yanıtı
+    // Idempotency-Key tekrarı; ilk iş bitmişse kaydı da döner
+    Replayed { message_id: String, job: Option<JobRecord> },
+}
+
+impl Submitted {
+    fn into_response(self) -> HttpResponse {
+        match self {
+            Submitted::Accepted(message_id) => HttpResponse::Accepted().json(JobAccepted::new(&message_id)),
+            Submitted::Replied(reply) => HttpResponse::Ok().json(reply),
+            Submitted::Replayed { message_id, job } => {
+                let mut response = match job {
+                    Some(job) => HttpResponse::Ok().json(job),
+                    None => HttpResponse::Accepted().json(JobAccepted::new(&message_id)),
+                };
+                response.headers_mut().insert(
+                    http::header::HeaderName::from_static("idempotent-replayed"),
+                    http::header::HeaderValue::from_static("true"),
+                );
+                response
+            }
+        }
+    }
+}
+
+pub struct AppState {
+    bus: Arc<dyn MessageBus>,
+    jwt_secret: String,
+    sync_timeout: Duration,
+    jobs: JobStore,
+    idempotency: IdempotencyStore,
+    backpressure: Backpressure,
+    rate_limiter: RateLimiter,
+    callbacks: CallbackAllowList,
+    field_policy: FieldDecryptPolicy,
+}
+
+const DEFAULT_SYNC_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_JOB_RETENTION_SECS: u64 = 3600;
+const DEFAULT_MAX_PAYLOAD_BYTES: usize = 1024 * 1024;
+const DEFAULT_IDEMPOTENCY_WINDOW_SECS: u64 = 86400;
+const DEFAULT_QUEUE_SHED_DEPTH: u32 = 5000;
+const DEFAULT_QUEUE_MAX_DEPTH: u32 = 10000;
+const DEFAULT_QUEUE_POLL_SECS: u64 = 1;
+const DEFAULT_RETRY_AFTER_SECS: u64 = 5;
+const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:50051";
+
+// Idempotency-Key varsa aynı kullanıcı + anahtar + gövde + mode + callback_url için ilk iş döndürülür
+#[allow(clippy::too_many_arguments)]
+async fn submit_job(
+    state: &AppState,
+    claims: &Claims,
+    options: &SubmitOptions,
+    idempotency_key: &IdempotencyKey,
+    trace: &TraceContext,
+    operation: Operation,
+    data: String,
+) -> Result<Submitted, ServiceError> {
+    let message_id = Uuid::new_v4().to_string();
+
+    // Processor'ın tanımadığı iş kuyruğa hiç yazılmaz
+    if operation == Operation::Unknown {
+        return Err(ServiceError::BadRequest("Desteklenmeyen operasyon".to_string()));
+    }
+
+    if let Some(callback_url) = &options.callback_url {
+        state.callbacks.check(&claims.sub, callback_url)?;
+    }
+
+    if let IdempotencyKey(Some(key)) = idempotency_key {
+        let fingerprint = fingerprint(operation.as_str(), options.mode.as_str(), options.callback_url.as_deref(), &data);
+        match state.idempotency.begin(&claims.sub, key, fingerprint, &message_id).await {
+            IdempotencyOutcome::New => {}
+            IdempotencyOutcome::Replay(original_id, age) => return replay_job(state, claims, &original_id, age).await,
+            IdempotencyOutcome::Conflict => {
+                return Err(ServiceError::Conflict(
+                    "Idempotency-Key farklı bir istek (gövde, mode veya callback_url) ile kullanılmış".to_string()
+                ));
+            }
+        }
+    }
+
+    let result = publish_job(state, claims, options, trace, operation, data, message_id).await;
+
+    if let (Err(ServiceError::QueueError(_) | ServiceError::Overloaded(..) | ServiceError::RateLimited(..)), IdempotencyKey(Some(key))) = (&result, idempotency_key) {
+        state.idempotency.release(&claims.sub, key).await;
+    }
+    result
+}
+
+// Tekrarlanan istek için ilk işin message_id'si ve varsa sonucu döner. Kayıt
+// yoksa ya ilk istek henüz kaydı yazmamıştır ya da kayıt saklama süresini aşmıştır;
+// anahtarın yaşı ikisini ayırır
+async fn replay_job(state: &AppState, claims: &Claims, message_id: &str, age: Duration) -> Result<Submitted, ServiceError> {
+    let job = match state.jobs.get(message_id, &claims.sub).await {
+        Some(job) => Some(job).filter(|job| job.status != JobStatus::Processing),
+        None if age < state.jobs.retention() => None,
+        None => Some(JobRecord::expired(message_id, &claims.sub)),
+    };
+
+    Ok(Submitted::Replayed { message_id: message_id.to_string(), job })
+}
+
+// İşi kuyruğa yazar; async modda 202 ile message_id, sync modda
+// crypt-processor'ın yanıtı (en fazla sync_timeout kadar beklenir) döner
+#[allow(clippy::too_many_arguments)]
+async fn publish_job(
+    state: &AppState,
+    claims: &Claims,
+    options: &SubmitOptions,
+    trace: &TraceContext,
+    operation: Operation,
+    data: String,
+    message_id: String,
+) -> Result<Submitted, ServiceError> {
+    let queue = work_queue(operation);
+
+    let data_len = data.len() as u64;
+    let message = JobMessage {
+        schema_version: SCHEMA_VERSION,
+        id: message_id.clone(),
+        operation,
+        data,
+        subject: claims.sub.clone(),
+        callback_url: options.callback_url.clone(),
+        traceparent: Some(trace.child().header()),
+    };
+
+    let quota_key = format!("sub:{}", claims.sub);
+    state.backpressure.admit(queue, options.priority).await?;
+    state.rate_limiter.charge(&quota_key, &claims.tier, data_len).await?;
+    state.jobs.insert_processing(&message_id, &claims.sub).await;
+
+    // 202 yalnızca broker mesajı onayladıktan sonra döner
+    if options.mode == SubmitMode::Async {
+        if let Err(e) = state.bus.publish(queue, &message).await {
+            state.rate_limiter.refund(&quota_key, data_len).await;
+            return Err(e.into());
+        }
+        state.backpressure.note_published(queue).await;
+        JOBS_PUBLISHED.with_label_values(&[queue]).inc();
+        tracing::info!(message_id = %message_id, queue, %operation, "İş kuyruğa yazıldı");
+
+        return Ok(Submitted::Accepted(message_id));
+    }
+
+    let response = state.bus.request(queue, &message, state.sync_timeout).await;
+    // Zaman aşımında iş kuyruğa yazılmıştır, yalnızca yanıt gelmemiştir
+    if matches!(response, Ok(_) | Err(BusError::Timeout(_))) {
+        state.backpressure.note_published(queue).await;
+        JOBS_PUBLISHED.with_label_values(&[queue]).inc();
+        tracing::info!(message_id = %message_id, queue, %operation, replied = response.is_ok(), "Senkron iş kuyruğa yazıldı");
+    } else {
+        state.rate_limiter.refund(&quota_key, data_len).await;
+    }
+    let response = response.map_err(|e| match e {
+        BusError::Timeout(timeout) => ServiceError::Timeout(format!(
+            "{} saniye içinde yanıt alınamadı (message_id: {})",
+            timeout.as_secs(), message_id
+        )),
+        e => e.into(),
+    })?;
+
+    let response: JobReply = serde_json::from_slice(&response)
+        .map_err(|e| ServiceError::SerializationError(e.to_string()))?;
+    Ok(Submitted::Replied(response))
+}
+
+fn to_job_data<T: Serialize>(request: &T) -> Result<String, ServiceError> {
+    serde_json::to_string(request)
+        .map_err(|e| ServiceError::SerializationError(e.to_string()))
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "Metni hibrit RSA + AES-GCM ile şifreler",
+    request_body = String,
+    params(SubmitOptions, IdempotencyKey),
+    responses(SubmitResponses),
+    security(("bearer_auth" = []))
+)]
+#[post("/encrypt")]
+async fn encrypt(
+    data: web::Json<String>,
+    claims: web::ReqData<Claims>,
+    options: web::Query<SubmitOptions>,
+    idempotency_key: IdempotencyKey,
+    trace: TraceContext,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    data.validated()?;
+    submit_job(&state, &claims, &options, &idempotency_key, &trace, Operation::Encrypt, data.into_inner()).await
+        .map(Submitted::into_response)
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "EncryptedData zarfını çözer",
+    request_body = EncryptedData,
+    params(SubmitOptions, IdempotencyKey),
+    responses(SubmitResponses),
+    security(("bearer_auth" = []))
+)]
+#[post("/decrypt")]
+async fn decrypt(
+    encrypted: web::Json<EncryptedData>,
+    claims: web::ReqData<Claims>,
+    options: web::Query<SubmitOptions>,
+    idempotency_key: IdempotencyKey,
+    trace: TraceContext,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    encrypted.validated()?;
+    let data = to_job_data(&encrypted.into_inner())?;
+    submit_job(&state, &claims, &options, &idempotency_key, &trace, Operation::Decrypt, data).await
+        .map(Submitted::into_response)
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "JSON belgesinde verilen yollardaki alanları şifreler",
+    request_body = FieldCryptRequest,
+    params(SubmitOptions, IdempotencyKey),
+    responses(SubmitResponses),
+    security(("bearer_auth" = []))
+)]
+#[post("/encrypt-fields")]
+async fn encrypt_fields(
+    request: web::Json<FieldCryptRequest>,
+    claims: web::ReqData<Claims>,
+    options: web::Query<SubmitOptions>,
+    idempotency_key: IdempotencyKey,
+    trace: TraceContext,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    request.validated()?;
+    let data = to_job_data(&request.into_inner())?;
+    submit_job(&state, &claims, &options, &idempotency_key, &trace, Operation::EncryptFields, data).await
+        .map(Submitted::into_response)
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "JSON belgesinde verilen yollardan FIELD_DECRYPT_POLICY'nin çağırana izin verdiklerini çözer; diğerleri şifreli kalır",
+    request_body = FieldCryptRequest,
+    params(SubmitOptions, IdempotencyKey),
+    responses(SubmitResponses),
+    security(("bearer_auth" = []))
+)]
+#[post("/decrypt-fields")]
+async fn decrypt_fields(
+    request: web::Json<FieldCryptRequest>,
+    claims: web::ReqData<Claims>,
+    options: web::Query<SubmitOptions>,
+    idempotency_key: IdempotencyKey,
+    trace: TraceContext,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    request.validated()?;
+    let mut request = request.into_inner();
+    request.paths = state.field_policy.authorize(&claims, &request.paths)?;
+    let data = to_job_data(&request)?;
+    submit_job(&state, &claims, &options, &idempotency_key, &trace, Operation::DecryptFields, data).await
+        .map(Submitted::into_response)
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "Değeri format koruyarak (FF1) tokenize eder",
+    request_body = TokenizeRequest,
+    params(SubmitOptions, IdempotencyKey),
+    responses(SubmitResponses),
+    security(("bearer_auth" = []))
+)]
+#[post("/tokenize")]
+async fn tokenize(
+    request: web::Json<TokenizeRequest>,
+    claims: web::ReqData<Claims>,
+    options: web::Query<SubmitOptions>,
+    idempotency_key: IdempotencyKey,
+    trace: TraceContext,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    request.validated()?;
+    let data = to_job_data(&request.into_inner())?;
+    submit_job(&state, &claims, &options, &idempotency_key, &trace, Operation::Tokenize, data).await
+        .map(Submitted::into_response)
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "Tokenı orijinal değere çevirir",
+    request_body = TokenizeRequest,
+    params(SubmitOptions, IdempotencyKey),
+    responses(SubmitResponses),
+    security(("bearer_auth" = []))
+)]
+#[post("/detokenize")]
+async fn detokenize(
+    request: web::Json<TokenizeRequest>,
+    claims: web::ReqData<Claims>,
+    options: web::Query<SubmitOptions>,
+    idempotency_key: IdempotencyKey,
+    trace: TraceContext,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    request.validated()?;
+    let data = to_job_data(&request.into_inner())?;
+    submit_job(&state, &claims, &options, &idempotency_key, &trace, Operation::Detokenize, data).await
+        .map(Submitted::into_response)
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "Eski sistemin AES-256-CTR + HMAC-SHA256 zarfını çözer; bu biçimde şifreleme yapılmaz",
+    request_body = LegacyEncryptedData,
+    params(SubmitOptions, IdempotencyKey),
+    responses(SubmitResponses),
+    security(("bearer_auth" = []))
+)]
+#[post("/decrypt-legacy")]
+async fn decrypt_legacy(
+    encrypted: web::Json<LegacyEncryptedData>,
+    claims: web::ReqData<Claims>,
+    options: web::Query<SubmitOptions>,
+    idempotency_key: IdempotencyKey,
+    trace: TraceContext,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    encrypted.validated()?;
+    let data = to_job_data(&encrypted.into_inner())?;
+    submit_job(&state, &claims, &options, &idempotency_key, &trace, Operation::DecryptLegacy, data).await
+        .map(Submitted::into_response)
+}
+
+#[utoipa::path(tag = "health", responses((status = 200, description = "Süreç ayakta")))]
+#[get("/healthz")]
+async fn healthz() -> HttpResponse {
+    health::liveness()
+}
+
+// Kimlik doğrulama gerektirmez; yük dengeleyici ve orkestratör tarafından yoklanır
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "Tüm bağımlılıklar hazır"),
+        (status = 503, description = "En az bir kontrol başarısız; gövde kontrol dökümüdür"),
+    )
+)]
+#[get("/readyz")]
+async fn readyz(state: web::Data<AppState>) -> HttpResponse {
+    let bus_ready = state.bus.is_ready();
+    HealthReport::new()
+        .check("message_bus", bus_ready, (!bus_ready).then(|| BusError::NotConnected.to_string()))
+        .check("job_results_consumer", state.jobs.is_consuming(), None)
+        .into_response()
+}
+
+#[utoipa::path(
+    tag = "jobs",
+    description = "Async işin durumu; yalnızca işi gönderen kullanıcı görebilir",
+    params(("message_id" = String, Path, description = "202 yanıtındaki message_id")),
+    responses(
+        (status = 200, description = "İş kaydı", body = JobRecord),
+        (status = 401, description = "Token yok veya geçersiz", body = ErrorBody),
+        (status = 404, description = "İş yok, süresi dolmuş veya başka kullanıcıya ait", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[get("/jobs/{message_id}")]
+async fn job_status(
+    message_id: web::Path<String>,
+    claims: web::ReqData<Claims>,
+    state: web::Data<AppState>
+) -> Result<HttpResponse, ServiceError> {
+    let job = state.jobs.get(&message_id, &claims.sub).await
+        .ok_or_else(|| ServiceError::NotFound(format!("İş bulunamadı: {}", message_id)))?;
+
+    Ok(HttpResponse::Ok().json(job))
+}
+
+fn env_secs(name: &str, default: u64) -> Duration {
+    let secs = env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default);
+    Duration::from_secs(secs)
+}
+
+fn env_u32(name: &str, default: u32) -> u32 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+// Arka plan görevlerini (sonuç tüketimi, kuyruk derinliği, temizlik) başlatır
+pub async fn app_state(bus: Arc<dyn MessageBus>) -> web::Data<AppState> {
+    let backpressure = Backpressure::new(Thresholds {
+        shed_depth: env_u32("QUEUE_SHED_DEPTH", DEFAULT_QUEUE_SHED_DEPTH),
+        max_depth: env_u32("QUEUE_MAX_DEPTH", DEFAULT_QUEUE_MAX_DEPTH),
+        retry_after: env_secs("QUEUE_RETRY_AFTER_SECS", DEFAULT_RETRY_AFTER_SECS),
+    });
+    backpressure.watch(bus.clone(), &WORK_QUEUES, env_secs("QUEUE_POLL_SECS", DEFAULT_QUEUE_POLL_SECS));
+
+    let rate_limiter = match env::var("RATE_LIMIT_TIERS") {
+        Ok(json) => RateLimiter::from_json(&json).expect("RATE_LIMIT_TIERS geçersiz"),
+        Err(_) => RateLimiter::new(default_tiers()),
+    };
+    let rate_limiter = match env::var("TRUSTED_PROXIES") {
+        Ok(list) => rate_limiter.with_trusted_proxies(
+            list.split(',')
+                .map(|ip| ip.trim().parse().expect("TRUSTED_PROXIES geçersiz"))
+                .collect()
+        ),
+        Err(_) => rate_limiter,
+    };
+    let purge_limiter = rate_limiter.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            purge_limiter.purge_idle().await;
+        }
+    });
+
+    let jobs = JobStore::new(env_secs("JOB_RETENTION_SECS", DEFAULT_JOB_RETENTION_SECS));
+    jobs.spawn_purge();
+    jobs.consume_results(bus.as_ref())
+        .await
+        .expect("İş sonucu aboneliği başlatılamadı");
+
+    let callbacks = match env::var("WEBHOOK_ALLOWLIST") {
+        Ok(json) => CallbackAllowList::from_json(&json).expect("WEBHOOK_ALLOWLIST geçersiz"),
+        Err(_) => CallbackAllowList::default(),
+    };
+
+    let field_policy = match env::var("FIELD_DECRYPT_POLICY") {
+        Ok(json) => FieldDecryptPolicy::from_json(&json).expect("FIELD_DECRYPT_POLICY geçersiz"),
+        Err(_) => FieldDecryptPolicy::default(),
+    };
+
+    let idempotency = IdempotencyStore::new(env_secs("IDEMPOTENCY_WINDOW_SECS", DEFAULT_IDEMPOTENCY_WINDOW_SECS));
+    let purge_store = idempotency.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            purge_store.purge_expired().await;
+        }
+    });
+
+    web::Data::new(AppState {
+        bus,
+        jwt_secret: jwt_secret(),
+        sync_timeout: env_secs("SYNC_TIMEOUT_SECS", DEFAULT_SYNC_TIMEOUT_SECS),
+        jobs,
+        idempotency,
+        backpressure,
+        rate_limiter,
+        callbacks,
+        field_policy,
+    })
+}
+
+// REST gövdesi ve gRPC mesajı için aynı sınır
+fn max_payload_bytes() -> usize {
+    env::var("MAX_PAYLOAD_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_PAYLOAD_BYTES)
+}
+
+// Rotalar ve extractor ayarları; AppState ayrıca app_data ile verilmelidir
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default()
+            .limit(max_payload_bytes())
+            .error_handler(json_error_handler))
+        .app_data(web::QueryConfig::default().error_handler(query_error_handler))
+        // Boş önekli kapsamdan önce kaydedilmeli, aksi halde JWT istenir
+        .service(healthz)
+        .service(readyz)
+        .service(service_metrics::metrics)
+        .service(SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", ApiDoc::openapi()))
+        .service(
+            web::scope("")
+                .wrap(from_fn(jwt_auth))
+                .wrap(from_fn(rate_limit))
+                .service(encrypt)
+                .service(decrypt)
+                .service(encrypt_fields)
+                .service(decrypt_fields)
+                .service(tokenize)
+                .service(detokenize)
+                .service(decrypt_legacy)
+                .service(job_status)
+        );
+}
+
+pub async fn run(bus: Arc<dyn MessageBus>) -> std::io::Result<()> {
+    let app_state = app_state(bus).await;
+
+    let grpc_addr = env::var("GRPC_ADDR").unwrap_or(DEFAULT_GRPC_ADDR.to_string());
+    let grpc_listener = tokio::net::TcpListener::bind(&grpc_addr).await?;
+    tracing::info!(addr = %grpc_addr, "gRPC sunucusu dinliyor");
+    let grpc = grpc::serve(app_state.clone(), grpc_listener);
+
+    let http = HttpServer::new(move || {
+        App::new()
+            .wrap(from_fn(track_requests))
+            .wrap(from_fn(trace_requests))
+            .app_data(app_state.clone())
+            .configure(configure)
+    })
+    .bind("127.0.0.1:8081")?
+    .run();
+
+    // Biri durursa servis tamamen kapanır
+    tokio::select! {
+        result = http => result,
+        result = grpc => result.map_err(std::io::Error::other),
+    }
+}
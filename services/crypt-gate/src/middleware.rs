@@ -1,10 +1,41 @@
 use std::fmt;
 use actix_web::{
-    error::ResponseError,
-    http::{header::ContentType, StatusCode},
-    HttpResponse,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    error::{JsonPayloadError, QueryPayloadError, ResponseError},
+    http::{header::{self, ContentType}, StatusCode},
+    middleware::Next,
+    web, Error, HttpMessage, HttpRequest, HttpResponse,
 };
-use serde_json::json;
+use jwt_validator::{bearer_token, validate_token};
+use serde::Serialize;
+use utoipa::ToSchema;
+use jwt_validator::Claims;
+use message_bus::BusError;
+use crate::ratelimit::{RateLimitStatus, DEFAULT_TIER};
+use crate::AppState;
+
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self { field: field.to_string(), message: message.into() }
+    }
+}
+
+// Tüm hata yanıtlarının gövdesi
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    #[schema(example = "Doğrulama hatası")]
+    pub error: String,
+    #[schema(example = "VALIDATION_ERROR")]
+    pub code: &'static str,
+    pub details: Vec<FieldError>,
+}
 
 #[derive(Debug)]
 pub enum ServiceError {
@@ -13,6 +44,17 @@ pub enum ServiceError {
     ServiceLockError(String),
     SerializationError(String),
     QueueError(String),
+    Overloaded(String, u64),    // mesaj, Retry-After (saniye)
+    RateLimited(String, RateLimitStatus),
+    Unauthorized(String),
+    Forbidden(String),
+    Timeout(String),
+    NotFound(String),
+    BadRequest(String),
+    ValidationError(Vec<FieldError>),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    Conflict(String),
 }
 
 impl fmt::Display for ServiceError {
@@ -23,33 +65,191 @@ impl fmt::Display for ServiceError {
             ServiceError::ServiceLockError(msg) => write!(f, "Servis kilidi hatası: {}", msg),
             ServiceError::SerializationError(msg) => write!(f, "Serileştirme hatası: {}", msg),
             ServiceError::QueueError(msg) => write!(f, "Kuyruk hatası: {}", msg),
+            ServiceError::Overloaded(msg, _) => write!(f, "Servis yoğun: {}", msg),
+            ServiceError::RateLimited(msg, _) => write!(f, "Sınır aşıldı: {}", msg),
+            ServiceError::Unauthorized(msg) => write!(f, "Yetkilendirme hatası: {}", msg),
+            ServiceError::Forbidden(msg) => write!(f, "Erişim reddedildi: {}", msg),
+            ServiceError::Timeout(msg) => write!(f, "Zaman aşımı: {}", msg),
+            ServiceError::NotFound(msg) => write!(f, "Bulunamadı: {}", msg),
+            ServiceError::BadRequest(msg) => write!(f, "Geçersiz istek: {}", msg),
+            ServiceError::ValidationError(details) => write!(f, "Doğrulama hatası: {} alan geçersiz", details.len()),
+            ServiceError::PayloadTooLarge(msg) => write!(f, "İstek çok büyük: {}", msg),
+            ServiceError::UnsupportedMediaType(msg) => write!(f, "Desteklenmeyen içerik tipi: {}", msg),
+            ServiceError::Conflict(msg) => write!(f, "Çakışma: {}", msg),
         }
     }
 }
 
 impl std::error::Error for ServiceError {}
 
+impl ServiceError {
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            ServiceError::EncryptionError(_) => "ENCRYPTION_ERROR",
+            ServiceError::DecryptionError(_) => "DECRYPTION_ERROR",
+            ServiceError::ServiceLockError(_) => "SERVICE_LOCK_ERROR",
+            ServiceError::SerializationError(_) => "SERIALIZATION_ERROR",
+            ServiceError::QueueError(_) => "QUEUE_ERROR",
+            ServiceError::Overloaded(..) => "QUEUE_OVERLOADED",
+            ServiceError::RateLimited(..) => "RATE_LIMITED",
+            ServiceError::Unauthorized(_) => "UNAUTHORIZED",
+            ServiceError::Forbidden(_) => "FORBIDDEN",
+            ServiceError::Timeout(_) => "TIMEOUT",
+            ServiceError::NotFound(_) => "NOT_FOUND",
+            ServiceError::BadRequest(_) => "BAD_REQUEST",
+            ServiceError::ValidationError(_) => "VALIDATION_ERROR",
+            ServiceError::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            ServiceError::UnsupportedMediaType(_) => "UNSUPPORTED_MEDIA_TYPE",
+            ServiceError::Conflict(_) => "IDEMPOTENCY_CONFLICT",
+        }
+    }
+
+    pub(crate) fn message(&self) -> String {
+        match self {
+            ServiceError::EncryptionError(msg)
+            | ServiceError::DecryptionError(msg)
+            | ServiceError::ServiceLockError(msg)
+            | ServiceError::SerializationError(msg)
+            | ServiceError::QueueError(msg)
+            | ServiceError::Overloaded(msg, _)
+            | ServiceError::RateLimited(msg, _)
+            | ServiceError::Unauthorized(msg)
+            | ServiceError::Forbidden(msg)
+            | ServiceError::Timeout(msg)
+            | ServiceError::NotFound(msg)
+            | ServiceError::BadRequest(msg)
+            | ServiceError::PayloadTooLarge(msg)
+            | ServiceError::UnsupportedMediaType(msg)
+            | ServiceError::Conflict(msg) => msg.to_string(),
+            ServiceError::ValidationError(_) => "İstek doğrulanamadı".to_string(),
+        }
+    }
+
+    pub(crate) fn details(&self) -> &[FieldError] {
+        match self {
+            ServiceError::ValidationError(details) => details,
+            _ => &[],
+        }
+    }
+}
+
 impl ResponseError for ServiceError {
     fn error_response(&self) -> HttpResponse {
-        let (error_message, error_code) = match self {
-            ServiceError::EncryptionError(msg) => (msg.to_string(), "ENCRYPTION_ERROR"),
-            ServiceError::DecryptionError(msg) => (msg.to_string(), "DECRYPTION_ERROR"),
-            ServiceError::ServiceLockError(msg) => (msg.to_string(), "SERVICE_LOCK_ERROR"),
-            ServiceError::SerializationError(msg) => (msg.to_string(), "SERIALIZATION_ERROR"),
-            ServiceError::QueueError(msg) => (msg.to_string(), "QUEUE_ERROR"),
-        };
-
-        HttpResponse::build(self.status_code())
-            .insert_header(ContentType::json())
-            .json(json!({
-                "error": error_message,
-                "code": error_code
-            }))
+        let mut response = HttpResponse::build(self.status_code());
+        response.insert_header(ContentType::json());
+        if let ServiceError::Overloaded(_, retry_after) = self {
+            response.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+        }
+        let mut response = response.json(ErrorBody {
+            error: self.message(),
+            code: self.code(),
+            details: self.details().to_vec(),
+        });
+        if let ServiceError::RateLimited(_, status) = self {
+            status.apply(response.headers_mut(), true);
+        }
+        response
     }
 
     fn status_code(&self) -> StatusCode {
-        match *self {
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        match self {
+            ServiceError::BadRequest(_) | ServiceError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            ServiceError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ServiceError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+            ServiceError::Conflict(_) => StatusCode::CONFLICT,
+            ServiceError::RateLimited(..) => StatusCode::TOO_MANY_REQUESTS,
+            ServiceError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            ServiceError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ServiceError::QueueError(_) | ServiceError::Overloaded(..) => StatusCode::SERVICE_UNAVAILABLE,
+            ServiceError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            ServiceError::EncryptionError(_)
+            | ServiceError::DecryptionError(_)
+            | ServiceError::ServiceLockError(_)
+            | ServiceError::SerializationError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<BusError> for ServiceError {
+    fn from(e: BusError) -> Self {
+        match e {
+            BusError::Timeout(_) => ServiceError::Timeout(e.to_string()),
+            e => ServiceError::QueueError(e.to_string()),
         }
     }
-} 
\ No newline at end of file
+}
+
+// actix extractor hatalarını ServiceError gövdesine çevirir
+pub fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> Error {
+    let error = match &err {
+        JsonPayloadError::Overflow { limit } | JsonPayloadError::OverflowKnownLength { limit, .. } => {
+            ServiceError::PayloadTooLarge(format!("İstek gövdesi {} byte sınırını aşıyor", limit))
+        }
+        JsonPayloadError::ContentType => {
+            ServiceError::UnsupportedMediaType("Content-Type application/json olmalıdır".to_string())
+        }
+        JsonPayloadError::Deserialize(e) if e.is_eof() && e.line() == 1 && e.column() == 0 => {
+            ServiceError::ValidationError(vec![FieldError::new("body", "İstek gövdesi boş")])
+        }
+        JsonPayloadError::Deserialize(e) => {
+            ServiceError::ValidationError(vec![FieldError::new("body", e.to_string())])
+        }
+        _ => ServiceError::BadRequest(err.to_string()),
+    };
+    error.into()
+}
+
+pub fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> Error {
+    ServiceError::ValidationError(vec![FieldError::new("query", err.to_string())]).into()
+}
+
+// key-gate'in verdiği HS256 token'ı doğrular, Claims'i request extension'larına ekler
+fn authenticate(req: &ServiceRequest, state: &AppState) -> Result<Claims, ServiceError> {
+    let header_value = req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    bearer_token(header_value)
+        .and_then(|token| validate_token(token, state.jwt_secret.as_bytes()))
+        .map_err(|e| ServiceError::Unauthorized(e.to_string()))
+}
+
+pub async fn jwt_auth(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let state = req.app_data::<web::Data<AppState>>()
+        .ok_or_else(|| ServiceError::Unauthorized("JWT yapılandırması bulunamadı".to_string()))?;
+
+    let claims = authenticate(&req, state)?;
+
+    req.extensions_mut().insert(claims);
+    next.call(req).await
+}
+
+// jwt_auth'tan önce çalışır; geçerli token yoksa istemci IP'si ile sınırlanır,
+// böylece kimliksiz istek selleri de kovaya takılır
+pub async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(state) = req.app_data::<web::Data<AppState>>().cloned() else {
+        return next.call(req).await;
+    };
+
+    let (key, tier) = match authenticate(&req, &state) {
+        Ok(claims) => (format!("sub:{}", claims.sub), claims.tier),
+        Err(_) => {
+            let real_ip = req.headers().get("x-real-ip").and_then(|value| value.to_str().ok());
+            let ip = state.rate_limiter.client_ip(req.peer_addr().map(|addr| addr.ip()), real_ip);
+            (format!("ip:{}", ip), DEFAULT_TIER.to_string())
+        }
+    };
+
+    let status = state.rate_limiter.check_rate(&key, &tier).await?;
+    let mut response = next.call(req).await?;
+    status.apply(response.headers_mut(), false);
+    Ok(response)
+}
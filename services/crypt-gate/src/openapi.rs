@@ -0,0 +1,164 @@
+use backend::crypt::EncryptedData;
+use backend::field::FieldCryptRequest;
+use backend::fpe::TokenizeRequest;
+use backend::legacy::LegacyEncryptedData;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{IntoResponses, Modify, OpenApi};
+use crate::backpressure::Priority;
+use crate::jobs::{JobAccepted, JobRecord, JobStatus};
+use protocol::{ErrorCode, JobReply};
+use crate::middleware::{ErrorBody, FieldError};
+use crate::SubmitMode;
+
+// İş gönderen uçların ortak yanıtları; yalnızca dokümantasyon için
+#[derive(IntoResponses)]
+#[allow(dead_code)]
+pub enum SubmitResponses {
+    #[response(status = 200, description = "mode=sync: crypt-processor'ın yanıtı")]
+    Done(JobReply),
+    #[response(status = 202, description = "mode=async: iş kuyruğa alındı, durum /jobs/{message_id} ile izlenir")]
+    Accepted(JobAccepted),
+    #[response(status = 400, description = "Geçersiz gövde, parametre veya Idempotency-Key")]
+    BadRequest(ErrorBody),
+    #[response(status = 401, description = "Token yok, geçersiz veya süresi dolmuş")]
+    Unauthorized(ErrorBody),
+    #[response(status = 403, description = "callback_url hesabın WEBHOOK_ALLOWLIST listesinde değil ya da istenen alanları çözme yetkisi yok")]
+    Forbidden(ErrorBody),
+    #[response(status = 409, description = "Idempotency-Key farklı bir gövde, mode veya callback_url ile kullanılmış")]
+    Conflict(ErrorBody),
+    #[response(status = 413, description = "Gövde MAX_PAYLOAD_BYTES sınırını aşıyor")]
+    PayloadTooLarge(ErrorBody),
+    #[response(status = 415, description = "Content-Type application/json değil")]
+    UnsupportedMediaType(ErrorBody),
+    #[response(
+        status = 429,
+        description = "Hız sınırı veya günlük kota aşıldı",
+        headers(("Retry-After" = u64, description = "Saniye"))
+    )]
+    RateLimited(ErrorBody),
+    #[response(
+        status = 503,
+        description = "Kuyruk bağlantısı yok veya kuyruk dolu",
+        headers(("Retry-After" = u64, description = "Saniye"))
+    )]
+    Unavailable(ErrorBody),
+    #[response(status = 504, description = "mode=sync: SYNC_TIMEOUT_SECS içinde yanıt alınamadı")]
+    Timeout(ErrorBody),
+}
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        // Cargo.toml'da lisans yok; boş lisans nesnesi yayınlanmasın
+        openapi.info.license = None;
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Crypt Gate", description = "Şifreleme işlerini kabul eden ve kuyruğa yazan API"),
+    paths(
+        crate::encrypt,
+        crate::decrypt,
+        crate::encrypt_fields,
+        crate::decrypt_fields,
+        crate::tokenize,
+        crate::detokenize,
+        crate::decrypt_legacy,
+        crate::job_status,
+        crate::healthz,
+        crate::readyz,
+    ),
+    components(schemas(
+        EncryptedData,
+        FieldCryptRequest,
+        TokenizeRequest,
+        LegacyEncryptedData,
+        JobAccepted,
+        JobReply,
+        ErrorCode,
+        JobRecord,
+        JobStatus,
+        SubmitMode,
+        Priority,
+        ErrorBody,
+        FieldError,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "jobs", description = "Şifreleme, çözme ve tokenizasyon işleri"),
+        (name = "health", description = "Sağlık kontrolleri"),
+    )
+)]
+pub struct ApiDoc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test::{call_service, init_service, read_body, TestRequest}, App};
+    use jwt_validator::{issue_token, jwt_secret, Claims};
+    use message_bus::MemoryBus;
+    use std::sync::Arc;
+
+    const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    // Kodda API değişip openapi.json güncellenmezse başarısız olur.
+    // Yeniden üretmek için: UPDATE_OPENAPI=1 cargo test -p crypt-gate openapi
+    #[test]
+    fn test_openapi_spec_up_to_date() {
+        let generated = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+        if std::env::var("UPDATE_OPENAPI").is_ok() {
+            std::fs::write(SPEC_PATH, &generated).unwrap();
+        }
+
+        let checked_in = std::fs::read_to_string(SPEC_PATH).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} güncel değil; UPDATE_OPENAPI=1 cargo test -p crypt-gate openapi ile yeniden üretin",
+            SPEC_PATH
+        );
+    }
+
+    // Spesifikasyondaki her uç gerçekten yönlendiriliyor olmalı
+    #[actix_web::test]
+    async fn test_openapi_paths_are_routed() {
+        let app = init_service(
+            App::new()
+                .app_data(crate::app_state(Arc::new(MemoryBus::new())).await)
+                .configure(crate::configure)
+        ).await;
+
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs() as i64;
+        let claims = Claims { sub: "admin".to_string(), iat: now, exp: now + 3600, tier: "admin".to_string() };
+        let token = issue_token(&claims, jwt_secret().as_bytes()).unwrap();
+
+        for (path, item) in ApiDoc::openapi().paths.paths {
+            let uri = path.replace("{message_id}", "yok");
+            let methods = [
+                (item.get.is_some(), TestRequest::get()),
+                (item.post.is_some(), TestRequest::post()),
+            ];
+
+            for (_, request) in methods.into_iter().filter(|(documented, _)| *documented) {
+                let request = request
+                    .uri(&uri)
+                    .insert_header(("Authorization", format!("Bearer {}", token)))
+                    .to_request();
+                let response = call_service(&app, request).await;
+                let status = response.status();
+                // Yönlendiricinin varsayılan 404'ü gövdesizdir; uygulamanın NOT_FOUND hatası değil
+                let body = read_body(response).await;
+                assert!(
+                    status != StatusCode::METHOD_NOT_ALLOWED && !(status == StatusCode::NOT_FOUND && body.is_empty()),
+                    "{} yönlendirilmiyor ({})", path, status
+                );
+            }
+        }
+    }
+}
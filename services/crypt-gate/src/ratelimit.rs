@@ -0,0 +1,293 @@
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use crate::middleware::ServiceError;
+
+pub const DEFAULT_TIER: &str = "standard";
+const SECS_PER_DAY: u64 = 86400;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct TierLimits {
+    pub requests_per_second: f64,
+    pub burst: u32,
+    pub daily_operations: u64,
+    pub daily_bytes: u64,
+}
+
+// "RateLimit-*" başlıklarına yazılan durum
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimitStatus {
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset: u64,     // saniye
+}
+
+impl RateLimitStatus {
+    pub fn apply(&self, headers: &mut HeaderMap, limited: bool) {
+        let headers_to_set = [
+            ("ratelimit-limit", self.limit),
+            ("ratelimit-remaining", self.remaining),
+            ("ratelimit-reset", self.reset),
+        ];
+        for (name, value) in headers_to_set {
+            headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+        }
+        if limited {
+            headers.insert(RETRY_AFTER, HeaderValue::from(self.reset.max(1)));
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Default)]
+struct DailyUsage {
+    day: u64,
+    operations: u64,
+    bytes: u64,
+}
+
+// Token bucket istek hızını, günlük kota ise kuyruğa yazılan iş sayısını ve
+// byte miktarını sınırlar. Anahtar JWT subject'idir, token yoksa istemci IP'si
+#[derive(Clone)]
+pub struct RateLimiter {
+    tiers: Arc<HashMap<String, TierLimits>>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+    usage: Arc<Mutex<HashMap<String, DailyUsage>>>,
+    trusted_proxies: Arc<Vec<IpAddr>>,
+}
+
+pub fn default_tiers() -> HashMap<String, TierLimits> {
+    HashMap::from([
+        (DEFAULT_TIER.to_string(), TierLimits {
+            requests_per_second: 5.0,
+            burst: 20,
+            daily_operations: 10_000,
+            daily_bytes: 100 * 1024 * 1024,
+        }),
+        ("admin".to_string(), TierLimits {
+            requests_per_second: 50.0,
+            burst: 200,
+            daily_operations: 1_000_000,
+            daily_bytes: 10 * 1024 * 1024 * 1024,
+        }),
+    ])
+}
+
+fn today() -> (u64, u64) {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    (now / SECS_PER_DAY, SECS_PER_DAY - now % SECS_PER_DAY)
+}
+
+impl RateLimiter {
+    pub fn new(mut tiers: HashMap<String, TierLimits>) -> Self {
+        if !tiers.contains_key(DEFAULT_TIER) {
+            tiers.insert(DEFAULT_TIER.to_string(), default_tiers()[DEFAULT_TIER]);
+        }
+        Self {
+            tiers: Arc::new(tiers),
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+            usage: Arc::new(Mutex::new(HashMap::new())),
+            trusted_proxies: Arc::new(Vec::new()),
+        }
+    }
+
+    // TRUSTED_PROXIES: X-Real-IP başlığına güvenilen vekillerin (Nginx) adresleri
+    pub fn with_trusted_proxies(mut self, proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = Arc::new(proxies);
+        self
+    }
+
+    // X-Real-IP yalnızca güvenilen bir vekilden gelirse dikkate alınır; aksi halde
+    // istemci başlığı her istekte değiştirip yeni bir kova alabilirdi
+    pub fn client_ip(&self, peer: Option<IpAddr>, real_ip: Option<&str>) -> String {
+        let forwarded = real_ip.and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+        match (peer, forwarded) {
+            (Some(peer), Some(client)) if self.trusted_proxies.contains(&peer) => client.to_string(),
+            (Some(peer), _) => peer.to_string(),
+            (None, _) => "unknown".to_string(),
+        }
+    }
+
+    // RATE_LIMIT_TIERS: {"standard": {"requests_per_second": 5, "burst": 20, ...}, ...}
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+
+    fn limits(&self, tier: &str) -> TierLimits {
+        self.tiers.get(tier).unwrap_or(&self.tiers[DEFAULT_TIER]).to_owned()
+    }
+
+    pub async fn check_rate(&self, key: &str, tier: &str) -> Result<RateLimitStatus, ServiceError> {
+        let limits = self.limits(tier);
+        let burst = limits.burst as f64;
+        let mut buckets = self.buckets.lock().await;
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: burst,
+            refilled_at: Instant::now(),
+        });
+
+        let elapsed = bucket.refilled_at.elapsed().as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limits.requests_per_second).min(burst);
+        bucket.refilled_at = Instant::now();
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        let missing = if allowed { burst - bucket.tokens } else { 1.0 - bucket.tokens };
+        let status = RateLimitStatus {
+            limit: limits.burst as u64,
+            remaining: bucket.tokens.floor() as u64,
+            reset: (missing / limits.requests_per_second).ceil() as u64,
+        };
+
+        if allowed {
+            Ok(status)
+        } else {
+            Err(ServiceError::RateLimited("İstek hızı sınırı aşıldı".to_string(), status))
+        }
+    }
+
+    // Kota yalnızca kuyruğa yazılacak işler için harcanır; günlük sayaç UTC gece yarısı sıfırlanır
+    pub async fn charge(&self, key: &str, tier: &str, bytes: u64) -> Result<(), ServiceError> {
+        let limits = self.limits(tier);
+        let (day, reset) = today();
+        let mut usage = self.usage.lock().await;
+        let entry = usage.entry(key.to_string()).or_default();
+
+        if entry.day != day {
+            *entry = DailyUsage { day, ..DailyUsage::default() };
+        }
+
+        if entry.operations + 1 > limits.daily_operations {
+            return Err(ServiceError::RateLimited(
+                "Günlük işlem kotası aşıldı".to_string(),
+                RateLimitStatus { limit: limits.daily_operations, remaining: 0, reset },
+            ));
+        }
+        if entry.bytes + bytes > limits.daily_bytes {
+            return Err(ServiceError::RateLimited(
+                "Günlük veri kotası aşıldı".to_string(),
+                RateLimitStatus { limit: limits.daily_bytes, remaining: limits.daily_bytes - entry.bytes, reset },
+            ));
+        }
+
+        entry.operations += 1;
+        entry.bytes += bytes;
+        Ok(())
+    }
+
+    // Kuyruğa yazılamayan işin harcadığı kota geri verilir; gün değiştiyse sayaç zaten sıfırlanmıştır
+    pub async fn refund(&self, key: &str, bytes: u64) {
+        let (day, _) = today();
+        if let Some(entry) = self.usage.lock().await.get_mut(key).filter(|entry| entry.day == day) {
+            entry.operations = entry.operations.saturating_sub(1);
+            entry.bytes = entry.bytes.saturating_sub(bytes);
+        }
+    }
+
+    // Dolmuş kovalar ve önceki günlerin sayaçları bellekten atılır
+    pub async fn purge_idle(&self) {
+        let (day, _) = today();
+        self.usage.lock().await.retain(|_, usage| usage.day == day);
+        self.buckets.lock().await.retain(|_, bucket| bucket.refilled_at.elapsed() < Duration::from_secs(3600));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(HashMap::from([
+            (DEFAULT_TIER.to_string(), TierLimits {
+                requests_per_second: 1.0,
+                burst: 2,
+                daily_operations: 3,
+                daily_bytes: 10,
+            }),
+            ("admin".to_string(), TierLimits {
+                requests_per_second: 1.0,
+                burst: 5,
+                daily_operations: 100,
+                daily_bytes: 1000,
+            }),
+        ]))
+    }
+
+    #[actix_web::test]
+    async fn test_token_bucket() {
+        let limiter = limiter();
+
+        assert_eq!(limiter.check_rate("alice", DEFAULT_TIER).await.unwrap().remaining, 1);
+        assert_eq!(limiter.check_rate("alice", DEFAULT_TIER).await.unwrap().remaining, 0);
+        match limiter.check_rate("alice", DEFAULT_TIER).await {
+            Err(ServiceError::RateLimited(_, status)) => assert_eq!(status.reset, 1),
+            _ => panic!("üçüncü istek reddedilmeliydi"),
+        }
+
+        // Kovalar kullanıcı bazındadır, kademe bilinmiyorsa standard kullanılır
+        assert!(limiter.check_rate("bob", "unknown").await.is_ok());
+        assert_eq!(limiter.check_rate("root", "admin").await.unwrap().limit, 5);
+    }
+
+    #[actix_web::test]
+    async fn test_daily_quota() {
+        let limiter = limiter();
+
+        assert!(limiter.charge("alice", DEFAULT_TIER, 4).await.is_ok());
+        assert!(limiter.charge("alice", DEFAULT_TIER, 7).await.is_err());
+        assert!(limiter.charge("alice", DEFAULT_TIER, 6).await.is_ok());
+        assert!(limiter.charge("alice", DEFAULT_TIER, 0).await.is_ok());
+        assert!(matches!(
+            limiter.charge("alice", DEFAULT_TIER, 0).await,
+            Err(ServiceError::RateLimited(_, RateLimitStatus { limit: 3, remaining: 0, .. }))
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_refund() {
+        let limiter = limiter();
+
+        assert!(limiter.charge("alice", DEFAULT_TIER, 6).await.is_ok());
+        assert!(limiter.charge("alice", DEFAULT_TIER, 6).await.is_err());
+        limiter.refund("alice", 6).await;
+        assert!(limiter.charge("alice", DEFAULT_TIER, 10).await.is_ok());
+
+        // Harcanmamış kota iade edilmez
+        limiter.refund("bob", 5).await;
+        assert!(limiter.usage.lock().await.get("bob").is_none());
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let limiter = limiter().with_trusted_proxies(vec![proxy]);
+
+        assert_eq!(limiter.client_ip(Some(proxy), Some("198.51.100.1")), "198.51.100.1");
+        assert_eq!(limiter.client_ip(Some(proxy), Some("bozuk")), "10.0.0.2");
+        // Doğrudan bağlanan istemcinin başlığı yok sayılır
+        assert_eq!(limiter.client_ip(Some(client), Some("198.51.100.1")), "203.0.113.7");
+        assert_eq!(limiter.client_ip(None, Some("198.51.100.1")), "unknown");
+    }
+
+    #[test]
+    fn test_tiers_from_json() {
+        let limiter = RateLimiter::from_json(
+            r#"{"premium": {"requests_per_second": 20, "burst": 40, "daily_operations": 5, "daily_bytes": 5}}"#
+        ).unwrap();
+
+        assert_eq!(limiter.limits("premium").burst, 40);
+        assert_eq!(limiter.limits("missing").burst, 20);
+    }
+}
@@ -0,0 +1,213 @@
+use backend::crypt::EncryptedData;
+use backend::field::{envelopes, FieldCryptRequest, JsonPath};
+use backend::fpe::TokenizeRequest;
+use backend::legacy::LegacyEncryptedData;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::Deserialize;
+use crate::middleware::{FieldError, ServiceError};
+
+// AES-GCM nonce ve etiket uzunlukları (byte)
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+// Eski AES-CTR IV'si ve HMAC-SHA256 uzunlukları (byte)
+const LEGACY_IV_LENGTH: usize = 16;
+const LEGACY_MAC_LENGTH: usize = 32;
+const MAX_PLAINTEXT_CHARS: usize = 512 * 1024;
+
+// İstek kuyruğa yazılmadan önce alan bazında doğrulanır
+pub trait Validate {
+    fn validate(&self) -> Vec<FieldError>;
+
+    fn validated(&self) -> Result<(), ServiceError> {
+        let errors = self.validate();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ServiceError::ValidationError(errors))
+        }
+    }
+}
+
+impl Validate for String {
+    fn validate(&self) -> Vec<FieldError> {
+        if self.is_empty() {
+            vec![FieldError::new("body", "Şifrelenecek veri boş olamaz")]
+        } else if self.chars().count() > MAX_PLAINTEXT_CHARS {
+            vec![FieldError::new("body", format!("Veri en fazla {} karakter olabilir", MAX_PLAINTEXT_CHARS))]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+fn decode_field(errors: &mut Vec<FieldError>, field: &str, value: &str) -> Option<Vec<u8>> {
+    if value.is_empty() {
+        errors.push(FieldError::new(field, "Boş olamaz"));
+        return None;
+    }
+    match BASE64.decode(value) {
+        Ok(bytes) => Some(bytes),
+        Err(_) => {
+            errors.push(FieldError::new(field, "Geçerli base64 değil"));
+            None
+        }
+    }
+}
+
+impl Validate for EncryptedData {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        decode_field(&mut errors, "encrypted_key", &self.encrypted_key);
+
+        if let Some(nonce) = decode_field(&mut errors, "nonce", &self.nonce) {
+            if nonce.len() != NONCE_LENGTH {
+                errors.push(FieldError::new("nonce", format!("{} byte olmalıdır, {} byte verildi", NONCE_LENGTH, nonce.len())));
+            }
+        }
+
+        if let Some(data) = decode_field(&mut errors, "data", &self.data) {
+            if data.len() < TAG_LENGTH {
+                errors.push(FieldError::new("data", format!("En az {} byte (kimlik doğrulama etiketi) olmalıdır", TAG_LENGTH)));
+            }
+        }
+
+        errors
+    }
+}
+
+impl Validate for LegacyEncryptedData {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if let Some(iv) = decode_field(&mut errors, "iv", &self.iv) {
+            if iv.len() != LEGACY_IV_LENGTH {
+                errors.push(FieldError::new("iv", format!("{} byte olmalıdır, {} byte verildi", LEGACY_IV_LENGTH, iv.len())));
+            }
+        }
+
+        decode_field(&mut errors, "data", &self.data);
+
+        if let Some(mac) = decode_field(&mut errors, "mac", &self.mac) {
+            if mac.len() != LEGACY_MAC_LENGTH {
+                errors.push(FieldError::new("mac", format!("{} byte olmalıdır, {} byte verildi", LEGACY_MAC_LENGTH, mac.len())));
+            }
+        }
+
+        errors
+    }
+}
+
+impl Validate for FieldCryptRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        if self.paths.is_empty() {
+            return vec![FieldError::new("paths", "En az bir yol belirtilmelidir")];
+        }
+
+        let errors: Vec<FieldError> = self.paths.iter()
+            .enumerate()
+            .filter_map(|(i, path)| JsonPath::parse(path).err()
+                .map(|e| FieldError::new(&format!("paths[{}]", i), e.to_string())))
+            .collect();
+        if !errors.is_empty() {
+            return errors;
+        }
+
+        // Yollardaki zarflar /decrypt gövdesi ile aynı kurallarla doğrulanır
+        let Ok(found) = envelopes(&self.document, &self.paths) else {
+            return Vec::new();
+        };
+        found.into_iter()
+            .flat_map(|(path, envelope)| {
+                let field = format!("document{}", &path[1..]);
+                match EncryptedData::deserialize(envelope) {
+                    Ok(encrypted) => encrypted.validate().into_iter()
+                        .map(|e| FieldError::new(&format!("{}.{}", field, e.field), e.message))
+                        .collect(),
+                    Err(_) => vec![FieldError::new(&field, "Geçersiz $enc zarfı")],
+                }
+            })
+            .collect()
+    }
+}
+
+impl Validate for TokenizeRequest {
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.value.is_empty() {
+            errors.push(FieldError::new("value", "Boş olamaz"));
+        }
+        if self.alphabet.as_deref().is_some_and(|alphabet| alphabet.chars().count() < 2) {
+            errors.push(FieldError::new("alphabet", "En az iki karakter içermelidir"));
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypted_data_validation() {
+        let valid = EncryptedData {
+            encrypted_key: BASE64.encode([1u8; 256]),
+            nonce: BASE64.encode([0u8; 12]),
+            data: BASE64.encode([0u8; 32]),
+        };
+        assert!(valid.validate().is_empty());
+
+        let invalid = EncryptedData {
+            encrypted_key: "%%%".to_string(),
+            nonce: BASE64.encode([0u8; 8]),
+            data: BASE64.encode([0u8; 4]),
+        };
+        let fields: Vec<_> = invalid.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, ["encrypted_key", "nonce", "data"]);
+    }
+
+    #[test]
+    fn test_legacy_encrypted_data_validation() {
+        let valid = LegacyEncryptedData {
+            iv: BASE64.encode([0u8; 16]),
+            data: BASE64.encode([0u8; 5]),
+            mac: BASE64.encode([0u8; 32]),
+        };
+        assert!(valid.validate().is_empty());
+
+        let invalid = LegacyEncryptedData {
+            iv: BASE64.encode([0u8; 12]),
+            data: String::new(),
+            mac: BASE64.encode([0u8; 20]),
+        };
+        let fields: Vec<_> = invalid.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, ["iv", "data", "mac"]);
+    }
+
+    #[test]
+    fn test_request_validation() {
+        assert_eq!(String::new().validate()[0].field, "body");
+        assert!("merhaba".to_string().validated().is_ok());
+
+        let request = FieldCryptRequest {
+            document: serde_json::json!({}),
+            paths: vec!["$.ad".to_string(), "ad[".to_string()],
+        };
+        assert_eq!(request.validate()[0].field, "paths[1]");
+
+        let request = FieldCryptRequest {
+            document: serde_json::json!({
+                "cards": [
+                    { "pan": { "$enc": { "encrypted_key": BASE64.encode([1u8; 256]), "nonce": "AAAA", "data": BASE64.encode([0u8; 32]) } } },
+                    { "pan": { "$enc": { "nonce": "AAAA" } } }
+                ]
+            }),
+            paths: vec!["$.cards[*].pan".to_string()],
+        };
+        let fields: Vec<_> = request.validate().into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, ["document.cards[0].pan.nonce", "document.cards[1].pan"]);
+
+        let request = TokenizeRequest { value: String::new(), alphabet: Some("0".to_string()), tweak: None };
+        assert!(matches!(request.validated(), Err(ServiceError::ValidationError(errors)) if errors.len() == 2));
+    }
+}
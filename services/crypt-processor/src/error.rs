@@ -1,13 +1,19 @@
 use std::fmt;
+use actix_web::{http::StatusCode, HttpResponse, ResponseError};
+use protocol::ErrorCode;
 use serde::Serialize;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum ProcessError {
     CryptError(String),
     FormatError(String),
     QueueError(String),
     WebSocketError(String),
     SerializationError(String),
+    Unauthorized(String),
+    UnsupportedOperation(String),
+    UnsupportedSchemaVersion(u32),
 }
 
 impl fmt::Display for ProcessError {
@@ -18,12 +24,57 @@ impl fmt::Display for ProcessError {
             ProcessError::QueueError(msg) => write!(f, "Kuyruk hatası: {}", msg),
             ProcessError::WebSocketError(msg) => write!(f, "WebSocket hatası: {}", msg),
             ProcessError::SerializationError(msg) => write!(f, "Serileştirme hatası: {}", msg),
+            ProcessError::Unauthorized(msg) => write!(f, "Yetkilendirme hatası: {}", msg),
+            ProcessError::UnsupportedOperation(operation) => write!(f, "Desteklenmeyen operasyon: {}", operation),
+            ProcessError::UnsupportedSchemaVersion(version) => write!(f, "Desteklenmeyen şema sürümü: {}", version),
         }
     }
 }
 
 impl std::error::Error for ProcessError {}
 
+impl ProcessError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ProcessError::CryptError(_) => ErrorCode::CryptError,
+            ProcessError::FormatError(_) => ErrorCode::FormatError,
+            ProcessError::QueueError(_) => ErrorCode::QueueError,
+            ProcessError::WebSocketError(_) => ErrorCode::WebsocketError,
+            ProcessError::SerializationError(_) => ErrorCode::SerializationError,
+            ProcessError::Unauthorized(_) => ErrorCode::Unauthorized,
+            ProcessError::UnsupportedOperation(_) => ErrorCode::UnsupportedOperation,
+            ProcessError::UnsupportedSchemaVersion(_) => ErrorCode::UnsupportedSchemaVersion,
+        }
+    }
+
+    // Display'deki önek olmadan
+    pub fn message(&self) -> String {
+        match self {
+            ProcessError::CryptError(msg)
+            | ProcessError::FormatError(msg)
+            | ProcessError::QueueError(msg)
+            | ProcessError::WebSocketError(msg)
+            | ProcessError::SerializationError(msg)
+            | ProcessError::Unauthorized(msg) => msg.clone(),
+            ProcessError::UnsupportedOperation(_) | ProcessError::UnsupportedSchemaVersion(_) => self.to_string(),
+        }
+    }
+}
+
+// HTTP uçlarında gövde ProcessResponse::error biçimindedir
+impl ResponseError for ProcessError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ProcessError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ProcessResponse::error(self))
+    }
+}
+
 #[derive(Serialize, Debug)]
 pub struct ProcessResponse {
     pub success: bool,
@@ -34,9 +85,10 @@ pub struct ProcessResponse {
 #[derive(Serialize, Debug)]
 pub struct ErrorDetail {
     pub message: String,
-    pub code: &'static str,
+    pub code: ErrorCode,
 }
 
+#[allow(dead_code)]
 impl ProcessResponse {
     pub fn success(result: String) -> Self {
         Self {
@@ -46,19 +98,11 @@ impl ProcessResponse {
         }
     }
 
-    pub fn error(error: ProcessError) -> Self {
-        let (message, code) = match &error {
-            ProcessError::CryptError(msg) => (msg.clone(), "CRYPT_ERROR"),
-            ProcessError::FormatError(msg) => (msg.clone(), "FORMAT_ERROR"),
-            ProcessError::QueueError(msg) => (msg.clone(), "QUEUE_ERROR"),
-            ProcessError::WebSocketError(msg) => (msg.clone(), "WEBSOCKET_ERROR"),
-            ProcessError::SerializationError(msg) => (msg.clone(), "SERIALIZATION_ERROR"),
-        };
-
+    pub fn error(error: &ProcessError) -> Self {
         Self {
             success: false,
             result: None,
-            error: Some(ErrorDetail { message, code }),
+            error: Some(ErrorDetail { message: error.message(), code: error.code() }),
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,238 @@
+use actix_web::web::Bytes;
+use futures_util::Stream;
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+// Last-Event-ID ile yeniden bağlanan istemciye tekrar gönderilebilecek son olay sayısı
+const REPLAY_CAPACITY: usize = 256;
+// Proxy'ler boştaki bağlantıyı kapatmasın diye yorum satırı gönderilir
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+// EventSource'un bağlantı koptuğunda bekleyeceği süre (ms)
+const RETRY_MS: u64 = 3000;
+
+#[derive(Clone, Debug)]
+struct Event {
+    id: u64,
+    subject: String,
+    data: String,   // WebSocket mesajıyla aynı JSON, tek satır
+}
+
+impl Event {
+    fn to_sse(&self) -> Bytes {
+        Bytes::from(format!("id: {}\ndata: {}\n\n", self.id, self.data))
+    }
+}
+
+// Tekrar tamponundan düşmüş olay olabilir; istemci eksik işleri /jobs ile sorgulamalı
+fn gap_event() -> Bytes {
+    Bytes::from_static(b"event: gap\ndata: {}\n\n")
+}
+
+struct Replay {
+    events: Vec<Event>,
+    gap: bool,
+}
+
+struct History {
+    last_id: u64,
+    recent: VecDeque<Event>,
+}
+
+// Tarayıcıdaki EventSource başlık gönderemez; JWT adres satırına (ve proxy loglarına)
+// düşmesin diye POST /events/session ile açılan oturumun opak kimliği çerezle taşınır
+struct Session {
+    subject: String,
+    expires_at: i64,    // oturumu açan JWT'nin exp değeri (unix saniye)
+}
+
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64)
+}
+
+// WebSocket'e giden sonuçların kullanıcı bazlı SSE yayını
+#[derive(Clone)]
+pub struct EventHub {
+    history: Arc<Mutex<History>>,
+    sender: broadcast::Sender<Event>,
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+}
+
+impl Default for EventHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self {
+            history: Arc::new(Mutex::new(History { last_id: 0, recent: VecDeque::new() })),
+            sender: broadcast::channel(REPLAY_CAPACITY).0,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // Oturum token ile aynı anda sona erer; süresi dolanlar yeni oturum açılırken temizlenir
+    pub async fn open_session(&self, subject: &str, expires_at: i64) -> String {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let mut sessions = self.sessions.lock().await;
+        let now = unix_now();
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(id.clone(), Session { subject: subject.to_string(), expires_at });
+        id
+    }
+
+    pub async fn session_subject(&self, id: &str) -> Option<String> {
+        self.sessions.lock().await
+            .get(id)
+            .filter(|session| session.expires_at > unix_now())
+            .map(|session| session.subject.clone())
+    }
+
+    pub async fn publish(&self, subject: &str, data: &str) {
+        let mut history = self.history.lock().await;
+        history.last_id += 1;
+        let event = Event { id: history.last_id, subject: subject.to_string(), data: data.to_string() };
+
+        if history.recent.len() == REPLAY_CAPACITY {
+            history.recent.pop_front();
+        }
+        history.recent.push_back(event.clone());
+        // Kilit altında gönderilir; abone tampon ile canlı akış arasında olay kaçırmaz
+        let _ = self.sender.send(event);
+    }
+
+    async fn subscribe(&self, subject: &str, last_event_id: Option<u64>) -> (Replay, broadcast::Receiver<Event>) {
+        let history = self.history.lock().await;
+        let receiver = self.sender.subscribe();
+
+        let Some(last_event_id) = last_event_id else {
+            return (Replay { events: Vec::new(), gap: false }, receiver);
+        };
+        let oldest = history.recent.front().map_or(history.last_id + 1, |event| event.id);
+        // Servis yeniden başladıysa istemcinin id'si bu süreçte anlamsızdır
+        let gap = last_event_id > history.last_id || last_event_id + 1 < oldest;
+        let after = if gap { 0 } else { last_event_id };
+
+        let events = history.recent.iter()
+            .filter(|event| event.id > after && event.subject == subject)
+            .cloned()
+            .collect();
+        (Replay { events, gap }, receiver)
+    }
+
+    // Önce Last-Event-ID'den sonraki kayıtlı olaylar, sonra canlı olaylar gönderilir
+    pub async fn stream(&self, subject: String, last_event_id: Option<u64>) -> impl Stream<Item = Result<Bytes, Infallible>> {
+        let (replay, mut live) = self.subscribe(&subject, last_event_id).await;
+        let (sender, receiver) = mpsc::channel::<Bytes>(16);
+
+        tokio::spawn(async move {
+            let mut head = vec![Bytes::from(format!("retry: {}\n\n", RETRY_MS))];
+            if replay.gap {
+                head.push(gap_event());
+            }
+            head.extend(replay.events.iter().map(Event::to_sse));
+            for chunk in head {
+                if sender.send(chunk).await.is_err() {
+                    return;
+                }
+            }
+
+            let mut keep_alive = tokio::time::interval(KEEP_ALIVE);
+            keep_alive.tick().await;
+            loop {
+                let chunk = tokio::select! {
+                    event = live.recv() => match event {
+                        Ok(event) if event.subject == subject => event.to_sse(),
+                        Ok(_) => continue,
+                        // Geride kalan istemci Last-Event-ID ile yeniden bağlanıp tampondan devam eder
+                        Err(broadcast::error::RecvError::Lagged(_)) | Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = keep_alive.tick() => Bytes::from_static(b": ping\n\n"),
+                };
+                // İstemci bağlantıyı kapattıysa
+                if sender.send(chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        futures_util::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|chunk| (Ok(chunk), receiver))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    async fn next_chunk(stream: &mut (impl Stream<Item = Result<Bytes, Infallible>> + Unpin)) -> String {
+        let chunk = tokio::time::timeout(Duration::from_secs(1), stream.next()).await.unwrap().unwrap().unwrap();
+        String::from_utf8(chunk.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_live_events_are_per_user() {
+        let hub = EventHub::new();
+        let mut stream = Box::pin(hub.stream("alice".to_string(), None).await);
+        assert_eq!(next_chunk(&mut stream).await, "retry: 3000\n\n");
+
+        hub.publish("bob", r#"{"message_id":"b"}"#).await;
+        hub.publish("alice", r#"{"message_id":"a"}"#).await;
+        assert_eq!(next_chunk(&mut stream).await, "id: 2\ndata: {\"message_id\":\"a\"}\n\n");
+    }
+
+    #[actix_web::test]
+    async fn test_resume_from_last_event_id() {
+        let hub = EventHub::new();
+        for id in 1..=3 {
+            hub.publish("alice", &format!("{{\"n\":{}}}", id)).await;
+        }
+
+        let mut stream = Box::pin(hub.stream("alice".to_string(), Some(1)).await);
+        next_chunk(&mut stream).await;
+        assert!(next_chunk(&mut stream).await.starts_with("id: 2\n"));
+        assert!(next_chunk(&mut stream).await.starts_with("id: 3\n"));
+
+        hub.publish("alice", "{}").await;
+        assert!(next_chunk(&mut stream).await.starts_with("id: 4\n"));
+    }
+
+    #[actix_web::test]
+    async fn test_sessions() {
+        let hub = EventHub::new();
+        let session = hub.open_session("alice", unix_now() + 60).await;
+        assert_eq!(hub.session_subject(&session).await.as_deref(), Some("alice"));
+        assert_eq!(hub.session_subject("unknown").await, None);
+
+        let expired = hub.open_session("bob", unix_now() - 1).await;
+        assert_eq!(hub.session_subject(&expired).await, None);
+        // Süresi dolan oturum bir sonraki açılışta silinir
+        hub.open_session("alice", unix_now() + 60).await;
+        assert!(!hub.sessions.lock().await.contains_key(&expired));
+    }
+
+    #[actix_web::test]
+    async fn test_gap_when_buffer_no_longer_has_events() {
+        let hub = EventHub::new();
+        for _ in 0..REPLAY_CAPACITY + 2 {
+            hub.publish("alice", "{}").await;
+        }
+
+        let (replay, _) = hub.subscribe("alice", Some(1)).await;
+        assert!(replay.gap);
+        assert_eq!(replay.events.len(), REPLAY_CAPACITY);
+
+        let (replay, _) = hub.subscribe("alice", Some(2)).await;
+        assert!(!replay.gap);
+
+        // Yeniden başlamış süreçten kalan id
+        let (replay, _) = hub.subscribe("alice", Some(10_000)).await;
+        assert!(replay.gap);
+    }
+}
@@ -0,0 +1,554 @@
+mod error;
+mod events;
+mod metrics;
+mod operations;
+mod webhook;
+
+use message_bus::{is_supported, Deliveries, Delivery, JobMessage, MessageBus, SCHEMA_VERSION, WORK_QUEUES};
+use protocol::JobReply;
+use futures_util::StreamExt;
+use backend::crypt::CryptService;
+use uuid::Uuid;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use error::ProcessError;
+use actix_web::{middleware::from_fn, web, App, HttpServer, get, post, Error, HttpRequest, HttpResponse};
+use actix_web::cookie::{time::Duration as CookieDuration, Cookie, SameSite};
+use actix_ws::Message as WsMessage;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use std::collections::HashMap;
+use health::HealthReport;
+use tokio::task::JoinHandle;
+use metrics::{CRYPTO_DURATION, PENDING_MESSAGES, WEBSOCKET_CONNECTIONS};
+use service_metrics::{track_requests, JOBS_ACKED, JOBS_CONSUMED};
+use std::time::Instant;
+use telemetry::{trace_requests, TraceContext};
+use tracing::Instrument;
+use jwt_validator::{bearer_token, jwt_secret, validate_token, Claims};
+use webhook::DeliveryLog;
+use events::EventHub;
+
+pub use operations::{OperationHandler, OperationRegistry};
+pub use webhook::{sign, WebhookConfig, WebhookSender, MESSAGE_ID_HEADER, SIGNATURE_HEADER};
+
+fn process_message(message: JobMessage, crypt_service: &CryptService, operations: &OperationRegistry, trace: &TraceContext) -> JobReply {
+    let started = Instant::now();
+    let result = if !is_supported(message.schema_version) {
+        // Daha yeni bir gateway'in uyumsuz zarfı; alanların anlamı değişmiş olabilir
+        Err(ProcessError::UnsupportedSchemaVersion(message.schema_version))
+    } else if let Some(handler) = operations.get(message.operation) {
+        handler.handle(crypt_service, &message.data)
+    } else {
+        // Gateway bu sürümün bilmediği bir operasyon yazmış
+        Err(ProcessError::UnsupportedOperation(message.operation.to_string()))
+    };
+
+    let outcome = if result.is_ok() { "success" } else { "failure" };
+    let elapsed = started.elapsed();
+    CRYPTO_DURATION
+        .with_label_values(&[message.operation.as_str(), outcome])
+        .observe(elapsed.as_secs_f64());
+
+    let duration_ms = elapsed.as_millis() as u64;
+    let traceparent = Some(trace.header());
+    match result {
+        Ok(data) => {
+            tracing::info!(outcome, duration_ms, "İş işlendi");
+            JobReply {
+                schema_version: SCHEMA_VERSION,
+                success: true,
+                message_id: message.id,
+                data: Some(data),
+                error: None,
+                code: None,
+                traceparent,
+            }
+        }
+        Err(err) => {
+            // Hata metinleri girdi içermez, loglanabilir
+            tracing::warn!(outcome, duration_ms, error = %err, "İş işlenemedi");
+            JobReply {
+                schema_version: SCHEMA_VERSION,
+                success: false,
+                message_id: message.id,
+                data: None,
+                error: Some(err.to_string()),
+                code: Some(err.code()),
+                traceparent,
+            }
+        }
+    }
+}
+
+// Kimliği doğrulanmış WebSocket bağlantısı; yalnızca sahibinin sonuçlarını alır
+struct Connection {
+    subject: String,
+    tx: mpsc::UnboundedSender<String>,
+}
+
+#[derive(Clone)]
+pub struct WebSocketManager {
+    connections: Arc<Mutex<HashMap<String, Connection>>>,
+    // Bağlı istemcisi olmayan kullanıcıların sonuçları, kullanıcı bazında
+    pending_messages: Arc<Mutex<HashMap<String, Vec<String>>>>,
+    // Aynı sonuçlar GET /events (SSE) ile de yayınlanır
+    events: EventHub,
+}
+
+impl Default for WebSocketManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebSocketManager {
+    pub fn new() -> Self {
+        Self {
+            connections: Arc::new(Mutex::new(HashMap::new())),
+            pending_messages: Arc::new(Mutex::new(HashMap::new())),
+            events: EventHub::new(),
+        }
+    }
+
+    async fn add_connection(&self, id: String, subject: String, tx: mpsc::UnboundedSender<String>) {
+        let mut connections = self.connections.lock().await;
+        tracing::info!(connection_id = %id, "WebSocket bağlantısı eklendi");
+
+        let mut pending = self.pending_messages.lock().await;
+        for message in pending.remove(&subject).unwrap_or_default() {
+            if let Err(e) = tx.send(message) {
+                tracing::warn!(connection_id = %id, error = %e, "Bekleyen mesaj gönderilemedi");
+            }
+        }
+        PENDING_MESSAGES.set(pending.values().map(Vec::len).sum::<usize>() as i64);
+
+        connections.insert(id, Connection { subject, tx });
+        WEBSOCKET_CONNECTIONS.set(connections.len() as i64);
+    }
+
+    // Kuyruk tüketicisinin yolunda çalışır, beklemez: kullanıcının bağlı istemcisi yoksa
+    // mesaj bir kez kullanıcının bekleme kuyruğuna alınır ve ilk bağlantısına gönderilir
+    async fn broadcast_message(&self, subject: &str, message: String) -> bool {
+        let mut connections = self.connections.lock().await;
+        let mut success = false;
+        connections.retain(|id, connection| {
+            if connection.subject != subject {
+                return true;
+            }
+            match connection.tx.send(message.clone()) {
+                Ok(_) => {
+                    tracing::debug!(connection_id = %id, "WebSocket mesajı gönderildi");
+                    success = true;
+                    true
+                },
+                Err(_) => {
+                    tracing::info!(connection_id = %id, "Kapalı WebSocket bağlantısı siliniyor");
+                    false
+                }
+            }
+        });
+        WEBSOCKET_CONNECTIONS.set(connections.len() as i64);
+        drop(connections);
+
+        if !success {
+            let mut pending = self.pending_messages.lock().await;
+            pending.entry(subject.to_string()).or_default().push(message);
+            PENDING_MESSAGES.set(pending.values().map(Vec::len).sum::<usize>() as i64);
+            tracing::debug!("Kullanıcının aktif WebSocket bağlantısı yok, mesaj bekleme kuyruğuna alındı");
+        }
+        success
+    }
+
+    async fn remove_connection(&self, id: &str) {
+        let mut connections = self.connections.lock().await;
+        let removed = connections.remove(id).is_some();
+        WEBSOCKET_CONNECTIONS.set(connections.len() as i64);
+        if removed {
+            tracing::info!(connection_id = %id, "WebSocket bağlantısı silindi");
+        }
+    }
+}
+
+// WebSocket yalnızca sahibinin sonuçlarını alır; kimlik /events ile aynı şekilde doğrulanır
+#[get("/ws")]
+async fn websocket(
+    req: HttpRequest,
+    body: web::Payload,
+    manager: web::Data<Arc<WebSocketManager>>,
+) -> Result<HttpResponse, Error> {
+    let subject = stream_subject(&req, &manager).await?;
+    let id = Uuid::new_v4().to_string();
+    let (response, mut session, mut msg_stream) = actix_ws::handle(&req, body)?;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    manager.add_connection(id.clone(), subject, tx).await;
+
+    tracing::info!(connection_id = %id, "WebSocket bağlantısı başlatıldı");
+
+    actix_web::rt::spawn(async move {
+        let mut ping_timer = tokio::time::interval(Duration::from_secs(5));
+
+        loop {
+            tokio::select! {
+                Some(msg) = msg_stream.next() => {
+                    match msg {
+                        Ok(WsMessage::Ping(bytes)) => {
+                            if let Err(e) = session.pong(&bytes).await {
+                                tracing::warn!(connection_id = %id, error = %e, "Ping yanıtı gönderilemedi");
+                                break;
+                            }
+                        }
+                        Ok(WsMessage::Close(_)) => {
+                            tracing::debug!(connection_id = %id, "WebSocket kapanış isteği alındı");
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::warn!(connection_id = %id, error = %e, "WebSocket hatası");
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                Some(msg) = rx.recv() => {
+                    if let Err(e) = session.text(msg).await {
+                        tracing::warn!(connection_id = %id, error = %e, "WebSocket mesajı gönderilemedi");
+                        break;
+                    }
+                }
+                _ = ping_timer.tick() => {
+                    if let Err(e) = session.ping(b"").await {
+                        tracing::warn!(connection_id = %id, error = %e, "Ping gönderilemedi");
+                        break;
+                    }
+                }
+            }
+        }
+
+        tracing::info!(connection_id = %id, "WebSocket bağlantısı kapandı");
+        manager.remove_connection(&id).await;
+    });
+
+    Ok(response)
+}
+
+// reply_to varsa (crypt-gate sync modu) yanıt doğrudan çağırana yazılır,
+// yoksa iş sahibinin WebSocket bağlantılarına yayınlanır
+async fn reply_to_caller(bus: &dyn MessageBus, delivery: &Delivery, response_json: &str) -> bool {
+    let Some(reply_to) = &delivery.reply_to else {
+        return false;
+    };
+
+    if let Err(e) = bus.reply(reply_to, response_json.as_bytes()).await {
+        tracing::warn!(error = %e, "RPC yanıtı gönderilemedi");
+    }
+    true
+}
+
+// crypt-gate'in iş durumu deposu için yayınlanan sonuç; deponun sonucu alıp
+// almadığını döner
+async fn publish_result(bus: &dyn MessageBus, message: &JobMessage, response: &JobReply) -> bool {
+    match bus.publish_result(&response.to_result(&message.subject)).await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(error = %e, "İş sonucu yayınlanamadı");
+            false
+        }
+    }
+}
+
+// Bir işin tüm logları; trace_id gateway isteğiyle aynıdır
+fn job_span(message: &JobMessage, trace: &TraceContext, queue: &str) -> tracing::Span {
+    tracing::info_span!(
+        "job",
+        trace_id = %trace.trace_id,
+        span_id = %trace.span_id,
+        message_id = %message.id,
+        operation = %message.operation,
+        queue,
+    )
+}
+
+async fn ack(delivery: Delivery, queue: &str) {
+    match delivery.ack().await {
+        Ok(()) => JOBS_ACKED.with_label_values(&[queue]).inc(),
+        Err(e) => tracing::warn!(error = %e, "İş ack edilemedi"),
+    }
+}
+
+// Kuyruk tüketicisinin paylaştığı bağımlılıklar
+#[derive(Clone)]
+struct Worker {
+    bus: Arc<dyn MessageBus>,
+    crypt_service: Arc<CryptService>,
+    operations: Arc<OperationRegistry>,
+    manager: Arc<WebSocketManager>,
+    webhooks: WebhookSender,
+}
+
+impl Worker {
+    async fn handle_messages(self, queue: &'static str, mut deliveries: Deliveries) {
+        while let Some(delivery) = deliveries.recv().await {
+            JOBS_CONSUMED.with_label_values(&[queue]).inc();
+            let trace = TraceContext::continue_from(delivery.message.traceparent.as_deref());
+            let span = job_span(&delivery.message, &trace, queue);
+            self.handle_delivery(delivery, &trace, queue).instrument(span).await;
+        }
+    }
+
+    async fn handle_delivery(&self, delivery: Delivery, trace: &TraceContext, queue: &'static str) {
+        // Sonuç yalnızca crypt-gate'in yanıt kuyruklarına yazılır; başka reply_to'lu iş işlenmeden düşürülür
+        if let Some(reply_to) = delivery.reply_to.as_ref().filter(|reply_to| !reply_to.is_gateway()) {
+            tracing::warn!(reply_to = %reply_to.to, "Geçersiz reply_to, iş reddedildi");
+            ack(delivery, queue).await;
+            return;
+        }
+
+        let message = delivery.message.clone();
+        let response = process_message(message.clone(), &self.crypt_service, &self.operations, trace);
+        let recorded = publish_result(self.bus.as_ref(), &message, &response).await;
+        let Ok(response_json) = serde_json::to_string(&response) else {
+            ack(delivery, queue).await;
+            return;
+        };
+
+        if reply_to_caller(self.bus.as_ref(), &delivery, &response_json).await {
+            ack(delivery, queue).await;
+            return;
+        }
+        // callback_url verilmişse sonuç WebSocket yerine webhook ile gider. Sonuç iş
+        // deposuna yazıldıysa iş hemen ack edilir, webhook denemeleri kuyruktaki
+        // prefetch sınırını tutmaz; yazılamadıysa iş teslimat sonuçlanana kadar tutulur
+        if self.webhooks.accepts(&message).await {
+            let held = if recorded {
+                ack(delivery, queue).await;
+                None
+            } else {
+                Some((delivery, queue))
+            };
+            self.webhooks.spawn_delivery(&message, &response_json, held);
+            return;
+        }
+
+        self.manager.events.publish(&message.subject, &response_json).await;
+        self.manager.broadcast_message(&message.subject, response_json).await;
+        ack(delivery, queue).await;
+    }
+}
+
+// Çalışan kuyruk tüketicileri; /readyz için izlenir
+pub struct Workers {
+    bus: Arc<dyn MessageBus>,
+    crypt_service: Arc<CryptService>,
+    consumers: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl Workers {
+    pub fn health(&self) -> HealthReport {
+        let bus_ready = self.bus.is_ready();
+        let mut report = HealthReport::new()
+            .check("message_bus", bus_ready, (!bus_ready).then(|| message_bus::BusError::NotConnected.to_string()))
+            .check("key_material", self.crypt_service.has_key_material(), None);
+
+        for (queue, handle) in &self.consumers {
+            let running = !handle.is_finished();
+            report = report.check(queue, running, (!running).then(|| "Tüketici durdu".to_string()));
+        }
+        report
+    }
+}
+
+// Kuyruk tüketicilerini başlatır; bağlantı koparsa bus tüketimi yeniden kurar.
+// Her iş kuyruğu, operasyonu registry'deki handler'a yönlendiren tek bir tüketiciyle işlenir
+pub async fn start_workers(
+    bus: Arc<dyn MessageBus>,
+    crypt_service: Arc<CryptService>,
+    operations: OperationRegistry,
+    manager: Arc<WebSocketManager>,
+    webhooks: WebhookSender,
+) -> Result<Workers, message_bus::BusError> {
+    let worker = Worker {
+        bus: bus.clone(),
+        crypt_service: crypt_service.clone(),
+        operations: Arc::new(operations),
+        manager,
+        webhooks,
+    };
+
+    let mut consumers = Vec::new();
+    for queue in WORK_QUEUES {
+        let deliveries = bus.consume(queue).await?;
+        consumers.push((queue, tokio::spawn(worker.clone().handle_messages(queue, deliveries))));
+    }
+
+    tracing::info!("Kuyruk tüketicileri başlatıldı");
+
+    Ok(Workers { bus, crypt_service, consumers })
+}
+
+#[get("/healthz")]
+async fn healthz() -> HttpResponse {
+    health::liveness()
+}
+
+#[get("/readyz")]
+async fn readyz(workers: web::Data<Workers>) -> HttpResponse {
+    workers.health().into_response()
+}
+
+// key-gate'in verdiği token; kullanıcıya özel uçlar için
+fn authenticate(req: &HttpRequest) -> Result<Claims, ProcessError> {
+    let header_value = req.headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    bearer_token(header_value)
+        .and_then(|token| validate_token(token, jwt_secret().as_bytes()))
+        .map_err(|e| ProcessError::Unauthorized(e.to_string()))
+}
+
+// SSE ve WebSocket oturum çerezi; nginx'te /api/events ve /ws/crypt/ altında
+// yayınlandıkları için yol varsayılanı köktür
+pub const EVENTS_COOKIE: &str = "crypt_events";
+const DEFAULT_EVENTS_COOKIE_PATH: &str = "/";
+
+// Tarayıcı istemcisi Bearer token ile SSE/WebSocket oturumu açar; token yerine HttpOnly
+// çerez taşınır, böylece JWT adres satırına ve erişim loglarına düşmez
+#[post("/events/session")]
+async fn open_event_session(
+    req: HttpRequest,
+    manager: web::Data<Arc<WebSocketManager>>,
+) -> Result<HttpResponse, ProcessError> {
+    let claims = authenticate(&req)?;
+    let session = manager.events.open_session(&claims.sub, claims.exp).await;
+    let path = std::env::var("EVENTS_COOKIE_PATH").unwrap_or_else(|_| DEFAULT_EVENTS_COOKIE_PATH.to_string());
+
+    let cookie = Cookie::build(EVENTS_COOKIE, session)
+        .path(path)
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .max_age(CookieDuration::seconds((claims.exp - events::unix_now()).max(0)))
+        .finish();
+    Ok(HttpResponse::NoContent().cookie(cookie).finish())
+}
+
+// WebSocket'e alternatif: kullanıcının iş sonuçları text/event-stream olarak.
+// Yeniden bağlanan istemcinin Last-Event-ID'sinden sonraki sonuçlar tampondan tekrar gönderilir
+#[get("/events")]
+async fn event_stream(
+    req: HttpRequest,
+    manager: web::Data<Arc<WebSocketManager>>,
+) -> Result<HttpResponse, ProcessError> {
+    let subject = stream_subject(&req, &manager).await?;
+    let last_event_id = req.headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok());
+
+    let stream = manager.events.stream(subject, last_event_id).await;
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "no-cache"))
+        // Nginx yanıtı tamponlamasın
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream))
+}
+
+// Başlık gönderebilen istemciler Bearer token, tarayıcılar (EventSource, WebSocket) oturum çerezi kullanır
+async fn stream_subject(req: &HttpRequest, manager: &WebSocketManager) -> Result<String, ProcessError> {
+    match req.cookie(EVENTS_COOKIE) {
+        Some(cookie) if !req.headers().contains_key(actix_web::http::header::AUTHORIZATION) => {
+            manager.events.session_subject(cookie.value()).await
+                .ok_or_else(|| ProcessError::Unauthorized("Oturum geçersiz veya süresi dolmuş".to_string()))
+        }
+        _ => Ok(authenticate(req)?.sub),
+    }
+}
+
+// Kullanıcının webhook teslimat denemeleri, en yenisi önce
+#[get("/webhooks/deliveries")]
+async fn webhook_deliveries(req: HttpRequest, log: web::Data<DeliveryLog>) -> Result<HttpResponse, ProcessError> {
+    let claims = authenticate(&req)?;
+    Ok(HttpResponse::Ok().json(log.for_subject(&claims.sub).await))
+}
+
+// Rotalar; Arc<WebSocketManager>, Workers ve DeliveryLog ayrıca app_data ile verilmelidir
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(websocket)
+        .service(open_event_session)
+        .service(event_stream)
+        .service(webhook_deliveries)
+        .service(service_metrics::metrics)
+        .service(healthz)
+        .service(readyz);
+}
+
+pub async fn run(bus: Arc<dyn MessageBus>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let ws_manager = Arc::new(WebSocketManager::new());
+    let ws_manager_data = web::Data::new(ws_manager.clone());
+    let webhooks = WebhookSender::new(WebhookConfig::from_env());
+    let delivery_log = web::Data::new(webhooks.log().clone());
+    let crypt_service = Arc::new(CryptService::from_env()?);
+    if !crypt_service.has_key_material() {
+        tracing::warn!("KEY_MATERIAL_FILE verilmedi; geçici anahtarlar kullanılıyor, /readyz hazır değil");
+    }
+    let workers = web::Data::new(start_workers(bus, crypt_service, OperationRegistry::default(), ws_manager, webhooks).await?);
+
+    // WebSocket sunucusu; CORS nginx'te, yalnızca ön yüzün origin'i için verilir
+    let websocket_task = HttpServer::new(move || {
+        App::new()
+            .app_data(ws_manager_data.clone())
+            .wrap(from_fn(track_requests))
+            .wrap(from_fn(trace_requests))
+            .app_data(workers.clone())
+            .app_data(delivery_log.clone())
+            .configure(configure)
+    })
+    .bind("127.0.0.1:8083")?
+    .run();
+
+    // WebSocket sunucusunu bekle
+    websocket_task.await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bağlı istemci yokken tüketici beklemez ve mesaj bir kez kuyruğa alınır
+    #[actix_web::test]
+    async fn test_broadcast_without_connections_does_not_wait() {
+        let manager = WebSocketManager::new();
+        let started = Instant::now();
+        assert!(!manager.broadcast_message("alice", "sonuç".to_string()).await);
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert_eq!(manager.pending_messages.lock().await["alice"], vec!["sonuç".to_string()]);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        manager.add_connection("c1".to_string(), "alice".to_string(), tx).await;
+        assert_eq!(rx.recv().await.as_deref(), Some("sonuç"));
+        assert!(manager.broadcast_message("alice", "ikinci".to_string()).await);
+        assert_eq!(rx.recv().await.as_deref(), Some("ikinci"));
+        assert!(manager.pending_messages.lock().await.is_empty());
+    }
+
+    // Sonuçlar ve bekleyen mesajlar yalnızca sahibinin bağlantılarına gider
+    #[actix_web::test]
+    async fn test_messages_are_per_user() {
+        let manager = WebSocketManager::new();
+        assert!(!manager.broadcast_message("bob", "bob'un sonucu".to_string()).await);
+
+        let (alice_tx, mut alice_rx) = mpsc::unbounded_channel();
+        manager.add_connection("c1".to_string(), "alice".to_string(), alice_tx).await;
+        assert!(manager.broadcast_message("alice", "alice'in sonucu".to_string()).await);
+        assert!(!manager.broadcast_message("bob", "ikinci".to_string()).await);
+
+        assert_eq!(alice_rx.recv().await.as_deref(), Some("alice'in sonucu"));
+        assert!(alice_rx.try_recv().is_err());
+        assert_eq!(manager.pending_messages.lock().await["bob"].len(), 2);
+    }
+}
@@ -0,0 +1,29 @@
+use prometheus::{register_histogram_vec, register_int_counter_vec, register_int_gauge, HistogramVec, IntCounterVec, IntGauge};
+use std::sync::LazyLock;
+
+pub static CRYPTO_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "crypto_operation_duration_seconds",
+        "Operasyon ve sonuca (success/failure) göre kriptografik işlem süresi",
+        &["operation", "outcome"]
+    ).unwrap()
+});
+
+pub static WEBSOCKET_CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("websocket_connections_active", "Açık WebSocket bağlantı sayısı").unwrap()
+});
+
+pub static PENDING_MESSAGES: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "websocket_pending_messages",
+        "Bağlantı olmadığı için bekletilen WebSocket mesajı sayısı"
+    ).unwrap()
+});
+
+pub static WEBHOOK_DELIVERIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "webhook_deliveries_total",
+        "Sonuca göre (delivered/failed) tamamlanan webhook teslimatları",
+        &["outcome"]
+    ).unwrap()
+});
@@ -0,0 +1,185 @@
+use backend::crypt::{CryptService, EncryptedData};
+use backend::field::FieldCryptRequest;
+use backend::fpe::TokenizeRequest;
+use backend::legacy::LegacyEncryptedData;
+use message_bus::Operation;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::error::ProcessError;
+
+// Tek bir operasyonun işlenişi: JobMessage.data girdi, başarılı yanıtın data alanı çıktı.
+// Yeni operasyon için message_bus::Operation'a değer eklenir ve burada handler kaydedilir
+pub trait OperationHandler: Send + Sync {
+    fn operation(&self) -> Operation;
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError>;
+}
+
+#[derive(Clone)]
+pub struct OperationRegistry {
+    handlers: HashMap<Operation, Arc<dyn OperationHandler>>,
+}
+
+// Yerleşik yedi operasyon
+impl Default for OperationRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .register(Encrypt)
+            .register(Decrypt)
+            .register(EncryptFields)
+            .register(DecryptFields)
+            .register(Tokenize)
+            .register(Detokenize)
+            .register(DecryptLegacy)
+    }
+}
+
+impl OperationRegistry {
+    pub fn empty() -> Self {
+        Self { handlers: HashMap::new() }
+    }
+
+    // Aynı operasyon için önceki handler'ın yerini alır
+    pub fn register(mut self, handler: impl OperationHandler + 'static) -> Self {
+        self.handlers.insert(handler.operation(), Arc::new(handler));
+        self
+    }
+
+    pub fn get(&self, operation: Operation) -> Option<&dyn OperationHandler> {
+        self.handlers.get(&operation).map(|handler| handler.as_ref())
+    }
+}
+
+fn parse<T: DeserializeOwned>(data: &str) -> Result<T, ProcessError> {
+    serde_json::from_str(data).map_err(|e| ProcessError::SerializationError(e.to_string()))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, ProcessError> {
+    serde_json::to_string(value).map_err(|e| ProcessError::SerializationError(e.to_string()))
+}
+
+fn crypt_error(e: impl ToString) -> ProcessError {
+    ProcessError::CryptError(e.to_string())
+}
+
+struct Encrypt;
+
+impl OperationHandler for Encrypt {
+    fn operation(&self) -> Operation {
+        Operation::Encrypt
+    }
+
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError> {
+        to_json(&crypt_service.encrypt_data(data).map_err(crypt_error)?)
+    }
+}
+
+struct Decrypt;
+
+impl OperationHandler for Decrypt {
+    fn operation(&self) -> Operation {
+        Operation::Decrypt
+    }
+
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError> {
+        let encrypted: EncryptedData = parse(data)?;
+        crypt_service.decrypt_data(&encrypted).map_err(crypt_error)
+    }
+}
+
+struct EncryptFields;
+
+impl OperationHandler for EncryptFields {
+    fn operation(&self) -> Operation {
+        Operation::EncryptFields
+    }
+
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError> {
+        let mut request: FieldCryptRequest = parse(data)?;
+        crypt_service.encrypt_fields(&mut request.document, &request.paths).map_err(crypt_error)?;
+        to_json(&request.document)
+    }
+}
+
+struct DecryptFields;
+
+impl OperationHandler for DecryptFields {
+    fn operation(&self) -> Operation {
+        Operation::DecryptFields
+    }
+
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError> {
+        let mut request: FieldCryptRequest = parse(data)?;
+        crypt_service.decrypt_fields(&mut request.document, &request.paths).map_err(crypt_error)?;
+        to_json(&request.document)
+    }
+}
+
+struct Tokenize;
+
+impl OperationHandler for Tokenize {
+    fn operation(&self) -> Operation {
+        Operation::Tokenize
+    }
+
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError> {
+        crypt_service.tokenize(&parse::<TokenizeRequest>(data)?).map_err(crypt_error)
+    }
+}
+
+struct Detokenize;
+
+impl OperationHandler for Detokenize {
+    fn operation(&self) -> Operation {
+        Operation::Detokenize
+    }
+
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError> {
+        crypt_service.detokenize(&parse::<TokenizeRequest>(data)?).map_err(crypt_error)
+    }
+}
+
+struct DecryptLegacy;
+
+impl OperationHandler for DecryptLegacy {
+    fn operation(&self) -> Operation {
+        Operation::DecryptLegacy
+    }
+
+    fn handle(&self, crypt_service: &CryptService, data: &str) -> Result<String, ProcessError> {
+        crypt_service.decrypt_legacy(&parse::<LegacyEncryptedData>(data)?).map_err(crypt_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Echo;
+
+    impl OperationHandler for Echo {
+        fn operation(&self) -> Operation {
+            Operation::Encrypt
+        }
+
+        fn handle(&self, _: &CryptService, data: &str) -> Result<String, ProcessError> {
+            Ok(data.to_string())
+        }
+    }
+
+    #[test]
+    fn test_builtin_operations_are_registered() {
+        let registry = OperationRegistry::default();
+        for operation in Operation::ALL {
+            assert_eq!(registry.get(operation).unwrap().operation(), operation);
+        }
+        assert!(registry.get(Operation::Unknown).is_none());
+    }
+
+    #[test]
+    fn test_register_replaces_handler() {
+        let registry = OperationRegistry::default().register(Echo);
+        let output = registry.get(Operation::Encrypt).unwrap().handle(&CryptService::new(), "merhaba").unwrap();
+        assert_eq!(output, "merhaba");
+    }
+}
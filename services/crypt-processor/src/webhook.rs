@@ -0,0 +1,352 @@
+use hmac::{Hmac, Mac};
+use message_bus::{Delivery, JobMessage};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tracing::Instrument;
+use crate::metrics::WEBHOOK_DELIVERIES;
+
+pub const SIGNATURE_HEADER: &str = "X-Crypt-Signature";
+pub const MESSAGE_ID_HEADER: &str = "X-Crypt-Message-Id";
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 6;
+const DEFAULT_INITIAL_BACKOFF_MS: u64 = 1000;
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Teslimat günlüğünde tutulan son deneme sayısı
+const LOG_CAPACITY: usize = 1000;
+
+// İmza sırları kullanıcı (JWT sub) başınadır (WEBHOOK_SECRETS): {"alice": "<sır>"}.
+// Bir alıcı kendi sırrıyla başka hesabın sonucunu imzalayamaz. Hiç sır yoksa webhook
+// teslimatı kapalıdır; sırrı olmayan kullanıcının sonucu WebSocket/SSE ile gider
+#[derive(Clone)]
+pub struct WebhookConfig {
+    pub secrets: HashMap<String, String>,
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,  // her denemede ikiye katlanır, en fazla MAX_BACKOFF
+}
+
+impl WebhookConfig {
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str, default: u64| env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+
+        let secrets = match env::var("WEBHOOK_SECRETS") {
+            Ok(json) => serde_json::from_str(&json).expect("WEBHOOK_SECRETS geçersiz"),
+            Err(_) => HashMap::new(),
+        };
+
+        Self {
+            secrets,
+            max_attempts: env_u64("WEBHOOK_MAX_ATTEMPTS", DEFAULT_MAX_ATTEMPTS as u64) as u32,
+            initial_backoff: Duration::from_millis(env_u64("WEBHOOK_BACKOFF_MS", DEFAULT_INITIAL_BACKOFF_MS)),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+// "t=<unix saniye>,v1=<hex(HMAC-SHA256(secret, "<t>.<gövde>"))>"; zaman damgası imzaya
+// dahil olduğu için alıcı eski istekleri tekrar oynatılmış sayıp reddedebilir
+pub fn sign(secret: &[u8], timestamp: u64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC her anahtar uzunluğunu kabul eder");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("t={},v1={}", timestamp, hex::encode(mac.finalize().into_bytes()))
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DeliveryAttempt {
+    pub message_id: String,
+    #[serde(skip)]
+    pub subject: String,
+    pub url: String,
+    pub attempt: u32,
+    pub status: Option<u16>,    // HTTP durum kodu; bağlantı hatasında yok
+    pub error: Option<String>,
+    pub delivered: bool,
+    pub at: u64,                // unix saniye
+}
+
+#[derive(Clone, Default)]
+pub struct DeliveryLog {
+    attempts: Arc<Mutex<VecDeque<DeliveryAttempt>>>,
+}
+
+impl DeliveryLog {
+    async fn record(&self, attempt: DeliveryAttempt) {
+        let mut attempts = self.attempts.lock().await;
+        if attempts.len() == LOG_CAPACITY {
+            attempts.pop_front();
+        }
+        attempts.push_back(attempt);
+    }
+
+    // En yeni deneme önce
+    pub async fn for_subject(&self, subject: &str) -> Vec<DeliveryAttempt> {
+        self.attempts.lock().await.iter()
+            .rev()
+            .filter(|attempt| attempt.subject == subject)
+            .cloned()
+            .collect()
+    }
+}
+
+enum Outcome {
+    Delivered,
+    Retry,
+    GiveUp,     // istemci hatası; tekrar denemek sonucu değiştirmez
+}
+
+fn classify(status: reqwest::StatusCode) -> Outcome {
+    if status.is_success() {
+        Outcome::Delivered
+    } else if status.is_server_error() || status == reqwest::StatusCode::REQUEST_TIMEOUT || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        Outcome::Retry
+    } else {
+        Outcome::GiveUp
+    }
+}
+
+// İş sonucunu callback_url'e imzalı POST ile iletir
+#[derive(Clone)]
+pub struct WebhookSender {
+    client: reqwest::Client,
+    config: WebhookConfig,
+    log: DeliveryLog,
+}
+
+impl WebhookSender {
+    pub fn new(config: WebhookConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            // Yönlendirme izin listesi dışındaki bir adrese gidebilir
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("HTTP istemcisi oluşturulamadı");
+
+        if config.secrets.is_empty() {
+            tracing::warn!("WEBHOOK_SECRETS verilmedi; webhook teslimatı kapalı, sonuçlar WebSocket/SSE ile gider");
+        }
+        Self { client, config, log: DeliveryLog::default() }
+    }
+
+    pub fn log(&self) -> &DeliveryLog {
+        &self.log
+    }
+
+    // Sonuç webhook ile mi gidecek: callback_url verilmiş ve kullanıcının imza sırrı var
+    pub async fn accepts(&self, message: &JobMessage) -> bool {
+        let Some(url) = &message.callback_url else {
+            return false;
+        };
+        if !self.has_secret(&message.subject) {
+            // Denemeyi günlüğe başarısız olarak yazar
+            self.deliver(&message.id, &message.subject, url, "").await;
+            return false;
+        }
+        true
+    }
+
+    // Teslimat ve tekrar denemeleri arka planda sürer, kuyruk tüketicisini bekletmez.
+    // Sonuç iş deposunda kayıtlıysa iş önceden ack edilmiştir; süreç denemeler
+    // sürerken yeniden başlarsa kalan denemeler yapılmaz, sonuç GET /jobs/{message_id}
+    // ile alınır. held verilirse (sonuç kaydedilemedi) iş teslimat sonuçlanınca ack
+    // edilir ve yeniden başlatmada broker işi tekrar teslim eder (alıcı
+    // X-Crypt-Message-Id ile tekrarları ayıklar)
+    pub fn spawn_delivery(&self, message: &JobMessage, body: &str, held: Option<(Delivery, &'static str)>) {
+        let Some(url) = message.callback_url.clone() else {
+            return;
+        };
+        let sender = self.clone();
+        let (id, subject, body) = (message.id.clone(), message.subject.clone(), body.to_string());
+        tokio::spawn(
+            async move {
+                sender.deliver(&id, &subject, &url, &body).await;
+                if let Some((delivery, queue)) = held {
+                    crate::ack(delivery, queue).await;
+                }
+            }
+            .instrument(tracing::Span::current())
+        );
+    }
+
+    fn has_secret(&self, subject: &str) -> bool {
+        self.config.secrets.contains_key(subject)
+    }
+
+    pub async fn deliver(&self, message_id: &str, subject: &str, url: &str, body: &str) -> bool {
+        let Some(secret) = self.config.secrets.get(subject) else {
+            // Sırrı olmayan hesap adına imzasız ya da başka sırla istek gönderilmez
+            self.log.record(DeliveryAttempt {
+                message_id: message_id.to_string(),
+                subject: subject.to_string(),
+                url: url.to_string(),
+                attempt: 0,
+                status: None,
+                error: Some("Hesap için webhook sırrı tanımlı değil".to_string()),
+                delivered: false,
+                at: unix_now(),
+            }).await;
+            WEBHOOK_DELIVERIES.with_label_values(&["failed"]).inc();
+            tracing::warn!("Webhook sırrı tanımlı değil, sonuç WebSocket/SSE ile gönderilecek");
+            return false;
+        };
+        let mut backoff = self.config.initial_backoff;
+
+        for attempt in 1..=self.config.max_attempts {
+            let signature = sign(secret.as_bytes(), unix_now(), body);
+            let response = self.client.post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, signature)
+                .header(MESSAGE_ID_HEADER, message_id)
+                .body(body.to_string())
+                .send()
+                .await;
+
+            let (status, error, outcome) = match response {
+                Ok(response) => {
+                    let status = response.status();
+                    (Some(status.as_u16()), None, classify(status))
+                }
+                Err(e) => (None, Some(e.to_string()), Outcome::Retry),
+            };
+            let delivered = matches!(outcome, Outcome::Delivered);
+            self.log.record(DeliveryAttempt {
+                message_id: message_id.to_string(),
+                subject: subject.to_string(),
+                url: url.to_string(),
+                attempt,
+                status,
+                error: error.clone(),
+                delivered,
+                at: unix_now(),
+            }).await;
+
+            match outcome {
+                Outcome::Delivered => {
+                    WEBHOOK_DELIVERIES.with_label_values(&["delivered"]).inc();
+                    tracing::info!(attempt, status, "Webhook teslim edildi");
+                    return true;
+                }
+                Outcome::GiveUp => break,
+                Outcome::Retry if attempt < self.config.max_attempts => {
+                    tracing::debug!(attempt, status, error, backoff_ms = backoff.as_millis() as u64, "Webhook yeniden denenecek");
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                Outcome::Retry => {}
+            }
+        }
+
+        WEBHOOK_DELIVERIES.with_label_values(&["failed"]).inc();
+        tracing::warn!(max_attempts = self.config.max_attempts, "Webhook teslim edilemedi");
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use std::sync::Mutex as StdMutex;
+
+    // Sırayla verilen durum kodlarını dönen ve istekleri kaydeden yerel alıcı
+    struct StandIn {
+        statuses: StdMutex<VecDeque<u16>>,
+        received: StdMutex<Vec<(String, String)>>,   // (imza, gövde)
+    }
+
+    async fn receive(req: HttpRequest, body: String, stand_in: web::Data<StandIn>) -> HttpResponse {
+        let signature = req.headers().get(SIGNATURE_HEADER).unwrap().to_str().unwrap().to_string();
+        stand_in.received.lock().unwrap().push((signature, body));
+        let status = stand_in.statuses.lock().unwrap().pop_front().unwrap_or(200);
+        HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap()).finish()
+    }
+
+    fn start_stand_in(statuses: &[u16]) -> (String, web::Data<StandIn>) {
+        let stand_in = web::Data::new(StandIn {
+            statuses: StdMutex::new(statuses.iter().copied().collect()),
+            received: StdMutex::new(Vec::new()),
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let data = stand_in.clone();
+        let server = HttpServer::new(move || App::new().app_data(data.clone()).route("/hook", web::post().to(receive)))
+            .workers(1)
+            .listen(listener)
+            .unwrap()
+            .run();
+        actix_web::rt::spawn(server);
+        (url, stand_in)
+    }
+
+    fn sender() -> WebhookSender {
+        WebhookSender::new(WebhookConfig {
+            secrets: HashMap::from([("alice".to_string(), "gizli".to_string())]),
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(10),
+        })
+    }
+
+    #[actix_web::test]
+    async fn test_retries_until_delivered() {
+        let (url, stand_in) = start_stand_in(&[500, 503, 200]);
+        let sender = sender();
+
+        assert!(sender.deliver("job-1", "alice", &url, r#"{"success":true}"#).await);
+
+        let received = stand_in.received.lock().unwrap().clone();
+        assert_eq!(received.len(), 3);
+        let (signature, body) = &received[2];
+        assert_eq!(body, r#"{"success":true}"#);
+        let timestamp: u64 = signature.strip_prefix("t=").unwrap().split(',').next().unwrap().parse().unwrap();
+        assert_eq!(signature, &sign(b"gizli", timestamp, body));
+
+        let log = sender.log().for_subject("alice").await;
+        assert_eq!(log.len(), 3);
+        assert!(log[0].delivered);
+        assert_eq!(log[2].status, Some(500));
+        assert!(sender.log().for_subject("bob").await.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_client_error_is_not_retried() {
+        let (url, stand_in) = start_stand_in(&[400]);
+        let sender = sender();
+
+        assert!(!sender.deliver("job-1", "alice", &url, "{}").await);
+        assert_eq!(stand_in.received.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_gives_up_after_max_attempts() {
+        // Dinlemeyen port: bağlantı hatası her denemede tekrar denenir
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let sender = sender();
+
+        assert!(!sender.deliver("job-1", "alice", &format!("http://127.0.0.1:{}/hook", port), "{}").await);
+        let log = sender.log().for_subject("alice").await;
+        assert_eq!(log.len(), 3);
+        assert!(log.iter().all(|attempt| attempt.status.is_none() && attempt.error.is_some()));
+    }
+
+    #[actix_web::test]
+    async fn test_subject_without_secret_is_not_delivered() {
+        let (url, stand_in) = start_stand_in(&[]);
+        let sender = sender();
+
+        assert!(!sender.deliver("job-1", "bob", &url, "{}").await);
+        assert!(stand_in.received.lock().unwrap().is_empty());
+        let log = sender.log().for_subject("bob").await;
+        assert_eq!(log.len(), 1);
+        assert!(!log[0].delivered && log[0].error.is_some());
+    }
+}
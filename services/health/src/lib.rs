@@ -0,0 +1,72 @@
+use actix_web::HttpResponse;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Check {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+// /readyz yanıtı: her bağımlılık için ayrı sonuç, biri bile başarısızsa servis hazır değildir
+#[derive(Serialize, Debug, Default)]
+pub struct HealthReport {
+    status: &'static str,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+impl HealthReport {
+    pub fn new() -> Self {
+        Self { status: "ok", checks: BTreeMap::new() }
+    }
+
+    pub fn check(mut self, name: &'static str, ok: bool, detail: Option<String>) -> Self {
+        if !ok {
+            self.status = "unavailable";
+        }
+        self.checks.insert(name, Check { ok, detail });
+        self
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.checks.values().all(|check| check.ok)
+    }
+
+    // Hazırsa 200, değilse 503; gövde her iki durumda da kontrol dökümüdür
+    pub fn into_response(self) -> HttpResponse {
+        if self.is_ready() {
+            HttpResponse::Ok().json(self)
+        } else {
+            HttpResponse::ServiceUnavailable().json(self)
+        }
+    }
+}
+
+// /healthz: süreç ayakta ve istek işleyebiliyor; bağımlılıklara bakılmaz
+pub fn liveness() -> HttpResponse {
+    HttpResponse::Ok().json(HealthReport::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_report_breakdown() {
+        let report = HealthReport::new()
+            .check("message_bus", true, None)
+            .check("key_material", false, Some("Anahtar yüklenmedi".to_string()));
+        assert!(!report.is_ready());
+
+        assert_eq!(serde_json::to_value(&report).unwrap(), json!({
+            "status": "unavailable",
+            "checks": {
+                "key_material": {"ok": false, "detail": "Anahtar yüklenmedi"},
+                "message_bus": {"ok": true}
+            }
+        }));
+        assert!(HealthReport::new().check("message_bus", true, None).is_ready());
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+
+// key-gate'in ürettiği, diğer servislerin doğruladığı token içeriği
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: i64,
+    pub iat: i64,
+    #[serde(default = "default_tier")]
+    pub tier: String,   // hız sınırı/kota kademesi
+}
+
+fn default_tier() -> String {
+    "standard".to_string()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TokenError {
+    Missing,
+    Expired,
+    IssuedInFuture,
+    Invalid(String),
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenError::Missing => write!(f, "Token bulunamadı"),
+            TokenError::Expired => write!(f, "Token süresi dolmuş"),
+            TokenError::IssuedInFuture => write!(f, "Token düzenlenme zamanı gelecekte"),
+            TokenError::Invalid(msg) => write!(f, "Geçersiz token: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+// Saat farkları için tolerans (saniye)
+const LEEWAY_SECS: i64 = 60;
+
+pub fn jwt_secret() -> String {
+    env::var("JWT_SECRET").unwrap_or("your-secret-key".to_string())
+}
+
+pub fn issue_token(claims: &Claims, secret: &[u8]) -> Result<String, TokenError> {
+    encode(&Header::new(Algorithm::HS256), claims, &EncodingKey::from_secret(secret))
+        .map_err(|e| TokenError::Invalid(e.to_string()))
+}
+
+// "Authorization: Bearer <token>" başlığından token'ı ayıklar
+pub fn bearer_token(header_value: &str) -> Result<&str, TokenError> {
+    header_value
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or(TokenError::Missing)
+}
+
+// İmza (yalnızca HS256), exp ve iat doğrulanır
+pub fn validate_token(token: &str, secret: &[u8]) -> Result<Claims, TokenError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.leeway = LEEWAY_SECS as u64;
+    validation.set_required_spec_claims(&["exp", "iat", "sub"]);
+
+    let data = decode::<Claims>(token, &DecodingKey::from_secret(secret), &validation)
+        .map_err(|e| match e.kind() {
+            ErrorKind::ExpiredSignature => TokenError::Expired,
+            _ => TokenError::Invalid(e.to_string()),
+        })?;
+
+    if data.claims.iat > Utc::now().timestamp() + LEEWAY_SECS {
+        return Err(TokenError::IssuedInFuture);
+    }
+
+    Ok(data.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test-secret";
+
+    fn claims(iat_offset: i64, exp_offset: i64) -> Claims {
+        let now = Utc::now().timestamp();
+        Claims { sub: "admin".to_string(), iat: now + iat_offset, exp: now + exp_offset, tier: "admin".to_string() }
+    }
+
+    #[test]
+    fn test_valid_token() {
+        let token = issue_token(&claims(0, 3600), SECRET).unwrap();
+        assert_eq!(validate_token(&token, SECRET).unwrap().sub, "admin");
+    }
+
+    #[test]
+    fn test_missing_tier_defaults_to_standard() {
+        let now = Utc::now().timestamp();
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &serde_json::json!({ "sub": "alice", "iat": now, "exp": now + 3600 }),
+            &EncodingKey::from_secret(SECRET),
+        ).unwrap();
+        assert_eq!(validate_token(&token, SECRET).unwrap().tier, "standard");
+    }
+
+    #[test]
+    fn test_rejected_tokens() {
+        let expired = issue_token(&claims(-7200, -3600), SECRET).unwrap();
+        assert_eq!(validate_token(&expired, SECRET).unwrap_err(), TokenError::Expired);
+
+        let future = issue_token(&claims(3600, 7200), SECRET).unwrap();
+        assert_eq!(validate_token(&future, SECRET).unwrap_err(), TokenError::IssuedInFuture);
+
+        let token = issue_token(&claims(0, 3600), SECRET).unwrap();
+        assert!(matches!(validate_token(&token, b"other-secret"), Err(TokenError::Invalid(_))));
+
+        // HS256 dışındaki algoritmalar kabul edilmez
+        let hs512 = encode(&Header::new(Algorithm::HS512), &claims(0, 3600), &EncodingKey::from_secret(SECRET)).unwrap();
+        assert!(matches!(validate_token(&hs512, SECRET), Err(TokenError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        assert_eq!(bearer_token("Bearer abc.def.ghi").unwrap(), "abc.def.ghi");
+        assert_eq!(bearer_token("Basic abc").unwrap_err(), TokenError::Missing);
+        assert_eq!(bearer_token("Bearer ").unwrap_err(), TokenError::Missing);
+    }
+}
@@ -1,53 +1,73 @@
-use actix_web::{web, App, HttpServer, post, HttpResponse, http};
+use actix_web::{middleware::from_fn, web, App, HttpServer, get, post, HttpResponse, http};
+use health::HealthReport;
 use actix_cors::Cors;
 use serde::{Deserialize, Serialize};
-use jsonwebtoken::{encode, EncodingKey, Header};
+use jwt_validator::{Claims, issue_token, jwt_secret};
 use chrono::{Utc, Duration};
-use std::env;
 use std::sync::Mutex;
 use backend::crypt::CryptService;
+use prometheus::{register_int_counter_vec, IntCounterVec};
+use service_metrics::track_requests;
+use telemetry::trace_requests;
+use std::sync::LazyLock;
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+use openapi::ApiDoc;
+
+mod openapi;
+
+static LOGIN_ATTEMPTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("login_attempts_total", "Sonuca (success/failure) göre giriş denemeleri", &["outcome"]).unwrap()
+});
 
 struct AppState {
     crypt_service: Mutex<CryptService>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct LoginRequest {
     username: String,
     password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct LoginResponse {
     token: String,
     public_key: String,  // RSA public key
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: String,
-    exp: i64,
-    iat: i64,
+#[derive(Debug, Serialize, ToSchema)]
+struct ErrorResponse {
+    #[schema(example = "Invalid username or password")]
+    error: String,
 }
 
+#[utoipa::path(
+    tag = "auth",
+    description = "Kullanıcı adı ve parola ile JWT (HS256, 1 gün) ve RSA public key alır",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Giriş başarılı", body = LoginResponse),
+        (status = 401, description = "Geçersiz kullanıcı adı veya parola", body = ErrorResponse),
+    )
+)]
 #[post("/login")]
 async fn login(
     req: web::Json<LoginRequest>,
     data: web::Data<AppState>
 ) -> HttpResponse {
     if req.username == "admin" && req.password == "password123" {
+        LOGIN_ATTEMPTS.with_label_values(&["success"]).inc();
+        tracing::info!(sub = %req.username, "Giriş başarılı");
         let now = Utc::now();
         let claims = Claims {
             sub: req.username.clone(),
             exp: (now + Duration::days(1)).timestamp(),
             iat: now.timestamp(),
+            tier: "admin".to_string(),
         };
 
-        let token = encode(
-            &Header::default(),
-            &claims,
-            &EncodingKey::from_secret(env::var("JWT_SECRET").unwrap_or("your-secret-key".to_string()).as_bytes()),
-        ).unwrap();
+        let token = issue_token(&claims, jwt_secret().as_bytes()).unwrap();
 
         let crypt_service = data.crypt_service.lock().unwrap();
         let public_key = crypt_service.get_public_key();
@@ -60,18 +80,58 @@ async fn login(
                 public_key
             })
     } else {
+        LOGIN_ATTEMPTS.with_label_values(&["failure"]).inc();
+        tracing::warn!("Geçersiz kullanıcı adı veya parola");
         HttpResponse::Unauthorized()
             .append_header(("Access-Control-Allow-Origin", "http://localhost:5173"))
             .append_header(("Access-Control-Allow-Credentials", "true"))
-            .json(serde_json::json!({
-                "error": "Invalid username or password"
-            }))
+            .json(ErrorResponse {
+                error: "Invalid username or password".to_string(),
+            })
     }
 }
 
+#[utoipa::path(tag = "health", responses((status = 200, description = "Süreç ayakta")))]
+#[get("/healthz")]
+async fn healthz() -> HttpResponse {
+    health::liveness()
+}
+
+#[utoipa::path(
+    tag = "health",
+    responses(
+        (status = 200, description = "Anahtar materyali yüklü"),
+        (status = 503, description = "Anahtar materyali yok; gövde kontrol dökümüdür"),
+    )
+)]
+#[get("/readyz")]
+async fn readyz(data: web::Data<AppState>) -> HttpResponse {
+    let (loaded, detail) = match data.crypt_service.lock() {
+        Ok(crypt_service) => (crypt_service.has_key_material(), None),
+        Err(_) => (false, Some("Anahtar servisi kilidi bozuk".to_string())),
+    };
+
+    HealthReport::new()
+        .check("key_material", loaded, detail)
+        .into_response()
+}
+
+fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(login)
+        .service(healthz)
+        .service(readyz)
+        .service(service_metrics::metrics)
+        .service(SwaggerUi::new("/docs/{_:.*}").url("/openapi.json", ApiDoc::openapi()));
+}
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let crypt_service = CryptService::new();
+    telemetry::init();
+    let crypt_service = CryptService::from_env()
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
+    if !crypt_service.has_key_material() {
+        tracing::warn!("KEY_MATERIAL_FILE verilmedi; geçici anahtarlar kullanılıyor, /readyz hazır değil");
+    }
     let app_state = web::Data::new(AppState {
         crypt_service: Mutex::new(crypt_service),
     });
@@ -90,9 +150,11 @@ async fn main() -> std::io::Result<()> {
             .supports_credentials();
 
         App::new()
+            .wrap(from_fn(track_requests))
+            .wrap(from_fn(trace_requests))
             .wrap(cors)
             .app_data(app_state.clone())
-            .service(login)
+            .configure(configure)
     })
     .bind("127.0.0.1:8082")?
     .run()
@@ -0,0 +1,74 @@
+use utoipa::{Modify, OpenApi};
+use crate::{ErrorResponse, LoginRequest, LoginResponse};
+
+struct Defaults;
+
+impl Modify for Defaults {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        // Cargo.toml'da lisans yok; boş lisans nesnesi yayınlanmasın
+        openapi.info.license = None;
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Key Gate", description = "Kimlik doğrulama ve JWT dağıtımı"),
+    paths(crate::login, crate::healthz, crate::readyz),
+    components(schemas(LoginRequest, LoginResponse, ErrorResponse)),
+    modifiers(&Defaults),
+    tags(
+        (name = "auth", description = "Giriş"),
+        (name = "health", description = "Sağlık kontrolleri"),
+    )
+)]
+pub struct ApiDoc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test::{call_service, init_service, TestRequest}, web, App};
+    use backend::crypt::CryptService;
+    use std::sync::Mutex;
+    use crate::AppState;
+
+    const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    // Kodda API değişip openapi.json güncellenmezse başarısız olur.
+    // Yeniden üretmek için: UPDATE_OPENAPI=1 cargo test -p key-gate openapi
+    #[test]
+    fn test_openapi_spec_up_to_date() {
+        let generated = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+        if std::env::var("UPDATE_OPENAPI").is_ok() {
+            std::fs::write(SPEC_PATH, &generated).unwrap();
+        }
+
+        let checked_in = std::fs::read_to_string(SPEC_PATH).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} güncel değil; UPDATE_OPENAPI=1 cargo test -p key-gate openapi ile yeniden üretin",
+            SPEC_PATH
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_openapi_paths_are_routed() {
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(AppState { crypt_service: Mutex::new(CryptService::new()) }))
+                .configure(crate::configure)
+        ).await;
+
+        for (path, item) in ApiDoc::openapi().paths.paths {
+            let request = if item.post.is_some() {
+                TestRequest::post().set_json(serde_json::json!({"username": "", "password": ""}))
+            } else {
+                TestRequest::get()
+            };
+            let response = call_service(&app, request.uri(&path).to_request()).await;
+            assert!(
+                ![StatusCode::NOT_FOUND, StatusCode::METHOD_NOT_ALLOWED].contains(&response.status()),
+                "{} yönlendirilmiyor ({})", path, response.status()
+            );
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use lapin::{
+    options::*, publisher_confirm::Confirmation, types::{AMQPValue, FieldTable}, BasicProperties, Channel, Consumer,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex};
+use crate::bus::*;
+use crate::supervisor::{supervise_amqp, AmqpSession, Backoff, Supervisor};
+use crate::topology::{declare_topology, persistent_properties, JOB_RESULTS_EXCHANGE};
+use crate::DEFAULT_PREFETCH;
+
+type PendingReplies = Arc<Mutex<HashMap<String, oneshot::Sender<Vec<u8>>>>>;
+
+// Yeniden bağlanma sırasında tüketim döngülerinin eski oturumu tekrar tekrar denememesi için
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+// RabbitMQ üzerinden MessageBus. Bağlantı Supervisor ile yönetilir; her yeni
+// bağlantıda kuyruklar, yayıncı onayları ve RPC yanıt kuyruğu yeniden kurulur
+pub struct AmqpBus {
+    supervisor: Supervisor<AmqpSession>,
+    reply_queue: Arc<RwLock<Option<String>>>,
+    pending: PendingReplies,
+    prefetch: u16,
+}
+
+impl AmqpBus {
+    pub fn connect(url: String, backoff: Backoff) -> Self {
+        let reply_queue = Arc::new(RwLock::new(None));
+        let pending: PendingReplies = Arc::new(Mutex::new(HashMap::new()));
+
+        let supervisor = supervise_amqp(url, backoff, {
+            let reply_queue = reply_queue.clone();
+            let pending = pending.clone();
+            move |session: AmqpSession| {
+                let reply_queue = reply_queue.clone();
+                let pending = pending.clone();
+                async move {
+                    declare_topology(&session.channel).await?;
+                    session.channel.confirm_select(ConfirmSelectOptions::default()).await?;
+                    let queue = start_reply_consumer(&session.channel, pending).await?;
+                    *reply_queue.write().unwrap() = Some(queue);
+                    Ok(())
+                }
+            }
+        });
+
+        Self { supervisor, reply_queue, pending, prefetch: DEFAULT_PREFETCH }
+    }
+
+    // consume ile açılan tüketicilerin ack edilmemiş iş sınırı (basic_qos)
+    pub fn with_prefetch(mut self, prefetch: u16) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    fn session(&self) -> Result<Arc<AmqpSession>, BusError> {
+        self.supervisor.current().ok_or(BusError::NotConnected)
+    }
+
+    async fn publish_job(&self, queue: &str, message: &JobMessage, properties: BasicProperties) -> Result<(), BusError> {
+        let payload = serde_json::to_vec(message).map_err(|e| BusError::Transport(e.to_string()))?;
+        let properties = match &message.traceparent {
+            Some(traceparent) => {
+                let mut headers = FieldTable::default();
+                headers.insert(TRACEPARENT_HEADER.into(), AMQPValue::LongString(traceparent.clone().into()));
+                properties.with_headers(headers)
+            }
+            None => properties,
+        };
+
+        // mandatory: kuyruğa yönlendirilemeyen mesaj sessizce kaybolmaz, geri döner
+        let confirmation = self.session()?.channel.basic_publish(
+            "",
+            queue,
+            BasicPublishOptions { mandatory: true, ..BasicPublishOptions::default() },
+            &payload,
+            properties,
+        ).await?.await?;
+
+        match confirmation {
+            Confirmation::Ack(None) | Confirmation::NotRequested => Ok(()),
+            Confirmation::Ack(Some(_)) => Err(BusError::Rejected(format!("{} kuyruğuna yönlendirilemedi", queue))),
+            Confirmation::Nack(_) => Err(BusError::Rejected("Broker mesajı onaylamadı".to_string())),
+        }
+    }
+
+    // Her (yeniden) bağlantıda tüketiciyi yeniden açar ve teslimatları tx'e aktarır;
+    // alıcı kapanınca biter
+    fn spawn_consumer<F, Fut>(&self, tx_closed: impl Fn() -> bool + Send + 'static, open: F)
+    where
+        F: Fn(Arc<AmqpSession>) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<bool, lapin::Error>> + Send,
+    {
+        let supervisor = self.supervisor.clone();
+        tokio::spawn(async move {
+            while !tx_closed() {
+                supervisor.wait_ready(true).await;
+                if let Some(session) = supervisor.current() {
+                    match open(session).await {
+                        Ok(true) => {}
+                        Ok(false) => break,
+                        Err(e) => tracing::warn!(error = %e, "Tüketici başlatılamadı"),
+                    }
+                }
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        });
+    }
+}
+
+async fn start_reply_consumer(channel: &Channel, pending: PendingReplies) -> Result<String, lapin::Error> {
+    // Bu bağlantıya özel geçici kuyruk; adı processor'ın kabul ettiği önekle başlar
+    let queue = channel.queue_declare(
+        &reply_address(),
+        QueueDeclareOptions { exclusive: true, auto_delete: true, ..QueueDeclareOptions::default() },
+        FieldTable::default(),
+    ).await?;
+    let reply_queue = queue.name().to_string();
+
+    let mut consumer = channel.basic_consume(
+        &reply_queue,
+        "",
+        BasicConsumeOptions { no_ack: true, ..BasicConsumeOptions::default() },
+        FieldTable::default(),
+    ).await?;
+
+    tokio::spawn(async move {
+        while let Some(Ok(delivery)) = consumer.next().await {
+            let Some(correlation_id) = delivery.properties.correlation_id() else {
+                continue;
+            };
+
+            // Zaman aşımına uğramış isteklerin geç yanıtları sessizce düşürülür
+            if let Some(tx) = pending.lock().await.remove(correlation_id.as_str()) {
+                let _ = tx.send(delivery.data);
+            }
+        }
+    });
+
+    Ok(reply_queue)
+}
+
+// false: alıcı kapandı, tüketim tamamen durmalı
+async fn forward_jobs(mut consumer: Consumer, tx: &mpsc::Sender<Delivery>) -> bool {
+    while let Some(Ok(delivery)) = consumer.next().await {
+        let mut message = match serde_json::from_slice::<JobMessage>(&delivery.data) {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!(error = %e, "Geçersiz iş mesajı reddedildi");
+                let _ = delivery.acker.reject(BasicRejectOptions { requeue: false }).await;
+                continue;
+            }
+        };
+
+        message.traceparent = delivery.properties.headers().as_ref()
+            .and_then(|headers| match headers.inner().get(TRACEPARENT_HEADER) {
+                Some(AMQPValue::LongString(value)) => Some(String::from_utf8_lossy(value.as_bytes()).into_owned()),
+                _ => None,
+            });
+
+        let reply_to = delivery.properties.reply_to().as_ref()
+            .zip(delivery.properties.correlation_id().as_ref())
+            .map(|(to, correlation_id)| ReplyTo {
+                to: to.to_string(),
+                correlation_id: correlation_id.to_string(),
+            });
+
+        let acker = delivery.acker.clone();
+        if tx.send(Delivery { message, reply_to, acker: Acker::Amqp(delivery.acker) }).await.is_err() {
+            let _ = acker.nack(BasicNackOptions { requeue: true, ..BasicNackOptions::default() }).await;
+            return false;
+        }
+    }
+    true
+}
+
+async fn forward_results(mut consumer: Consumer, tx: &mpsc::Sender<JobResult>) -> bool {
+    while let Some(Ok(delivery)) = consumer.next().await {
+        match serde_json::from_slice::<JobResult>(&delivery.data) {
+            Ok(result) => {
+                if tx.send(result).await.is_err() {
+                    return false;
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "Geçersiz iş sonucu mesajı"),
+        }
+    }
+    true
+}
+
+#[async_trait]
+impl MessageBus for AmqpBus {
+    fn is_ready(&self) -> bool {
+        self.supervisor.is_ready()
+    }
+
+    async fn publish(&self, queue: &str, message: &JobMessage) -> Result<(), BusError> {
+        self.publish_job(queue, message, persistent_properties()).await
+    }
+
+    async fn request(&self, queue: &str, message: &JobMessage, timeout: Duration) -> Result<Vec<u8>, BusError> {
+        let reply_queue = self.reply_queue.read().unwrap().clone().ok_or(BusError::NotConnected)?;
+
+        // Yanıt yayından önce kaydedilir, aksi halde hızlı bir yanıt kaçabilir
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(message.id.clone(), tx);
+
+        let properties = persistent_properties()
+            .with_reply_to(reply_queue.into())
+            .with_correlation_id(message.id.clone().into());
+
+        if let Err(e) = self.publish_job(queue, message, properties).await {
+            self.pending.lock().await.remove(&message.id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(BusError::Transport("RPC yanıt kanalı kapandı".to_string())),
+            Err(_) => {
+                self.pending.lock().await.remove(&message.id);
+                Err(BusError::Timeout(timeout))
+            }
+        }
+    }
+
+    async fn reply(&self, reply_to: &ReplyTo, payload: &[u8]) -> Result<(), BusError> {
+        let properties = BasicProperties::default()
+            .with_correlation_id(reply_to.correlation_id.clone().into());
+
+        self.session()?.channel.basic_publish(
+            "",
+            &reply_to.to,
+            BasicPublishOptions::default(),
+            payload,
+            properties,
+        ).await?;
+        Ok(())
+    }
+
+    async fn consume(&self, queue: &str) -> Result<Deliveries, BusError> {
+        let (tx, rx) = mpsc::channel(1);
+        let queue = queue.to_string();
+        let closed = tx.clone();
+        let prefetch = self.prefetch;
+
+        self.spawn_consumer(move || closed.is_closed(), move |session| {
+            let queue = queue.clone();
+            let tx = tx.clone();
+            async move {
+                // Sınır yoksa broker tüm birikmiş işi bu tüketiciye gönderir; işler ack
+                // edilmemiş sayılır ve queue_depth'in saydığı hazır mesaj sayısı sıfırda kalır
+                session.channel.basic_qos(prefetch, BasicQosOptions::default()).await?;
+                let consumer = session.channel.basic_consume(
+                    &queue,
+                    "",
+                    BasicConsumeOptions::default(),
+                    FieldTable::default(),
+                ).await?;
+                Ok(forward_jobs(consumer, &tx).await)
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn queue_depth(&self, queue: &str) -> Result<u32, BusError> {
+        // Passive declare var olmayan kuyrukta kanalı kapattığı için geçici kanal kullanılır
+        let channel = self.session()?.connection.create_channel().await?;
+        let state = channel.queue_declare(
+            queue,
+            QueueDeclareOptions { passive: true, ..QueueDeclareOptions::default() },
+            FieldTable::default(),
+        ).await;
+        let _ = channel.close(200, "OK").await;
+        Ok(state?.message_count())
+    }
+
+    async fn publish_result(&self, result: &JobResult) -> Result<(), BusError> {
+        let payload = serde_json::to_vec(result).map_err(|e| BusError::Transport(e.to_string()))?;
+        // mandatory: bağlı abone kuyruğu yoksa sonuç kaydedilmemiş sayılır
+        let confirmation = self.session()?.channel.basic_publish(
+            JOB_RESULTS_EXCHANGE,
+            "",
+            BasicPublishOptions { mandatory: true, ..BasicPublishOptions::default() },
+            &payload,
+            BasicProperties::default(),
+        ).await?.await?;
+
+        match confirmation {
+            Confirmation::Ack(None) | Confirmation::NotRequested => Ok(()),
+            Confirmation::Ack(Some(_)) => Err(BusError::Rejected("Sonucun abonesi yok".to_string())),
+            Confirmation::Nack(_) => Err(BusError::Rejected("Broker sonucu onaylamadı".to_string())),
+        }
+    }
+
+    async fn subscribe_results(&self) -> Result<Results, BusError> {
+        let (tx, rx) = mpsc::channel(1024);
+        let closed = tx.clone();
+
+        self.spawn_consumer(move || closed.is_closed(), move |session| {
+            let tx = tx.clone();
+            async move {
+                // Her abone kendi geçici kuyruğunu exchange'e bağlar
+                let queue = session.channel.queue_declare(
+                    "",
+                    QueueDeclareOptions { exclusive: true, auto_delete: true, ..QueueDeclareOptions::default() },
+                    FieldTable::default(),
+                ).await?;
+                session.channel.queue_bind(
+                    queue.name().as_str(),
+                    JOB_RESULTS_EXCHANGE,
+                    "",
+                    QueueBindOptions::default(),
+                    FieldTable::default(),
+                ).await?;
+                let consumer = session.channel.basic_consume(
+                    queue.name().as_str(),
+                    "",
+                    BasicConsumeOptions { no_ack: true, ..BasicConsumeOptions::default() },
+                    FieldTable::default(),
+                ).await?;
+                Ok(forward_results(consumer, &tx).await)
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Operation, SCHEMA_VERSION};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // Çalışan bir RabbitMQ gerektirir: cargo test -p message-bus -- --ignored
+    #[tokio::test]
+    #[ignore]
+    async fn test_prefetch_keeps_backlog_in_queue_depth() {
+        let url = std::env::var("RABBITMQ_URL").unwrap_or(crate::DEFAULT_RABBITMQ_URL.to_string());
+        let bus = AmqpBus::connect(url, Backoff::default()).with_prefetch(1);
+        bus.supervisor.wait_ready(true).await;
+
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let queue = format!("test_queue_{}", nanos);
+        bus.session().unwrap().channel.queue_declare(
+            &queue,
+            QueueDeclareOptions { auto_delete: true, ..QueueDeclareOptions::default() },
+            FieldTable::default(),
+        ).await.unwrap();
+
+        let job = |i: u32| JobMessage {
+            schema_version: SCHEMA_VERSION,
+            id: format!("job-{}", i),
+            operation: Operation::Encrypt,
+            data: "merhaba".to_string(),
+            subject: "admin".to_string(),
+            callback_url: None,
+            traceparent: None,
+        };
+        for i in 0..5 {
+            bus.publish(&queue, &job(i)).await.unwrap();
+        }
+
+        // Tüketici ilk işi ack etmeden tutar; broker prefetch kadarını gönderir
+        let mut deliveries = bus.consume(&queue).await.unwrap();
+        let in_progress = deliveries.recv().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(bus.queue_depth(&queue).await.unwrap(), 4);
+
+        for i in 5..10 {
+            bus.publish(&queue, &job(i)).await.unwrap();
+        }
+        assert_eq!(bus.queue_depth(&queue).await.unwrap(), 9);
+        in_progress.ack().await.unwrap();
+    }
+}
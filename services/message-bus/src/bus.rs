@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use std::fmt;
+use std::time::Duration;
+
+// Kuyruktaki iş ve sonuç zarfları; biçimleri protocol crate'indedir
+pub use protocol::{JobMessage, JobResult};
+
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+// crypt-gate'in yanıt kuyruğu (AMQP) ve inbox (NATS) adlarının öneki. Broker
+// yetkileri bu öneki yalnızca gateway kullanıcısına açmalıdır
+pub const REPLY_PREFIX: &str = "crypt-gate.reply.";
+
+// Senkron isteğin yanıtının yazılacağı yer (AMQP'de reply_to + correlation_id)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplyTo {
+    pub to: String,
+    pub correlation_id: String,
+}
+
+impl ReplyTo {
+    // Başka bir hedef, bir işin sonucunu başka bir kuyruğa yönlendirme girişimidir
+    pub fn is_gateway(&self) -> bool {
+        self.to.starts_with(REPLY_PREFIX) && self.to.len() > REPLY_PREFIX.len()
+    }
+}
+
+pub(crate) fn reply_address() -> String {
+    format!("{}{}", REPLY_PREFIX, uuid::Uuid::new_v4())
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BusError {
+    NotConnected,
+    Timeout(Duration),
+    Rejected(String),
+    Transport(String),
+}
+
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BusError::NotConnected => write!(f, "Mesaj kuyruğu bağlantısı yok"),
+            BusError::Timeout(timeout) => write!(f, "{} saniye içinde yanıt alınamadı", timeout.as_secs()),
+            BusError::Rejected(msg) => write!(f, "Mesaj reddedildi: {}", msg),
+            BusError::Transport(msg) => write!(f, "Mesaj kuyruğu hatası: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BusError {}
+
+impl From<lapin::Error> for BusError {
+    fn from(e: lapin::Error) -> Self {
+        BusError::Transport(e.to_string())
+    }
+}
+
+pub(crate) enum Acker {
+    Amqp(lapin::acker::Acker),
+    Nats(Box<async_nats::jetstream::Message>),
+    Memory(Option<tokio::sync::OwnedSemaphorePermit>),
+}
+
+// Tüketilen iş; işlendikten sonra ack edilmelidir
+pub struct Delivery {
+    pub message: JobMessage,
+    pub reply_to: Option<ReplyTo>,
+    pub(crate) acker: Acker,
+}
+
+impl Delivery {
+    pub async fn ack(self) -> Result<(), BusError> {
+        match self.acker {
+            Acker::Amqp(acker) => Ok(acker.ack(lapin::options::BasicAckOptions::default()).await?),
+            Acker::Nats(message) => message.ack().await.map_err(|e| BusError::Transport(e.to_string())),
+            // İzin bırakılınca tüketiciye sıradaki iş gönderilir
+            Acker::Memory(_permit) => Ok(()),
+        }
+    }
+}
+
+pub type Deliveries = tokio::sync::mpsc::Receiver<Delivery>;
+pub type Results = tokio::sync::mpsc::Receiver<JobResult>;
+
+// crypt-gate ile crypt-processor arasındaki iş ve sonuç akışı
+#[async_trait]
+pub trait MessageBus: Send + Sync {
+    fn is_ready(&self) -> bool;
+
+    // Broker mesajı onayladığında döner
+    async fn publish(&self, queue: &str, message: &JobMessage) -> Result<(), BusError>;
+
+    // İşi yayınlar ve işleyenin reply ile yazdığı yanıtı bekler
+    async fn request(&self, queue: &str, message: &JobMessage, timeout: Duration) -> Result<Vec<u8>, BusError>;
+
+    async fn reply(&self, reply_to: &ReplyTo, payload: &[u8]) -> Result<(), BusError>;
+
+    // Bağlantı koparsa tüketim yeniden bağlanınca kendiliğinden devam eder
+    async fn consume(&self, queue: &str) -> Result<Deliveries, BusError>;
+
+    async fn queue_depth(&self, queue: &str) -> Result<u32, BusError>;
+
+    // Sonuçlar tüm aboneler tarafından alınır (fanout). AMQP ve bellek bus'ında
+    // sonuç hiçbir aboneye (crypt-gate iş deposu) ulaşmazsa hata döner
+    async fn publish_result(&self, result: &JobResult) -> Result<(), BusError>;
+
+    async fn subscribe_results(&self) -> Result<Results, BusError>;
+}
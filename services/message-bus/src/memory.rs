@@ -0,0 +1,295 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, Notify, Semaphore};
+use crate::bus::*;
+
+const RESULTS_CAPACITY: usize = 1024;
+
+#[derive(Default)]
+struct Queue {
+    items: Mutex<VecDeque<(JobMessage, Option<ReplyTo>)>>,
+    notify: Notify,
+}
+
+impl Queue {
+    fn push(&self, item: (JobMessage, Option<ReplyTo>)) {
+        self.items.lock().unwrap().push_back(item);
+        self.notify.notify_one();
+    }
+}
+
+// Broker gerektirmeyen, tek süreç içinde çalışan bus: entegrasyon testleri ve
+// tek binary geliştirme modu için. Mesajlar kalıcı değildir
+pub struct MemoryBus {
+    queues: Mutex<HashMap<String, Arc<Queue>>>,
+    pending: Arc<Mutex<HashMap<String, oneshot::Sender<Vec<u8>>>>>,
+    results: broadcast::Sender<JobResult>,
+    prefetch: Option<u16>,
+}
+
+impl Default for MemoryBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryBus {
+    pub fn new() -> Self {
+        Self {
+            queues: Mutex::new(HashMap::new()),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            results: broadcast::channel(RESULTS_CAPACITY).0,
+            prefetch: None,
+        }
+    }
+
+    // Tüketici başına ack edilmemiş iş sınırı (AMQP basic_qos gibi); verilmezse sınır yok
+    pub fn with_prefetch(mut self, prefetch: u16) -> Self {
+        self.prefetch = Some(prefetch);
+        self
+    }
+
+    fn queue(&self, name: &str) -> Arc<Queue> {
+        self.queues.lock().unwrap().entry(name.to_string()).or_default().clone()
+    }
+}
+
+#[async_trait]
+impl MessageBus for MemoryBus {
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    async fn publish(&self, queue: &str, message: &JobMessage) -> Result<(), BusError> {
+        self.queue(queue).push((message.clone(), None));
+        Ok(())
+    }
+
+    async fn request(&self, queue: &str, message: &JobMessage, timeout: Duration) -> Result<Vec<u8>, BusError> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(message.id.clone(), tx);
+
+        let reply_to = ReplyTo { to: format!("{}memory", REPLY_PREFIX), correlation_id: message.id.clone() };
+        self.queue(queue).push((message.clone(), Some(reply_to)));
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(BusError::Transport("Yanıt kanalı kapandı".to_string())),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&message.id);
+                Err(BusError::Timeout(timeout))
+            }
+        }
+    }
+
+    async fn reply(&self, reply_to: &ReplyTo, payload: &[u8]) -> Result<(), BusError> {
+        // Zaman aşımına uğramış isteklerin geç yanıtları sessizce düşürülür
+        if let Some(tx) = self.pending.lock().unwrap().remove(&reply_to.correlation_id) {
+            let _ = tx.send(payload.to_vec());
+        }
+        Ok(())
+    }
+
+    async fn consume(&self, queue: &str) -> Result<Deliveries, BusError> {
+        let queue = self.queue(queue);
+        // Kapasite 1: bekleyen işler kuyrukta kalır, queue_depth onları sayar
+        let (tx, rx) = mpsc::channel(1);
+        let permits = self.prefetch.map(|prefetch| Arc::new(Semaphore::new(prefetch as usize)));
+
+        tokio::spawn(async move {
+            loop {
+                // Sınır doluysa iş ack edilene kadar kuyrukta bekler
+                let permit = match &permits {
+                    Some(permits) => Some(permits.clone().acquire_owned().await.expect("semafor kapatılmaz")),
+                    None => None,
+                };
+                let next = queue.items.lock().unwrap().pop_front();
+                let Some((message, reply_to)) = next else {
+                    queue.notify.notified().await;
+                    continue;
+                };
+
+                let delivery = Delivery { message, reply_to, acker: Acker::Memory(permit) };
+                if let Err(mpsc::error::SendError(delivery)) = tx.send(delivery).await {
+                    // Tüketici kapandı; iş bir sonraki tüketiciye kalır
+                    queue.items.lock().unwrap().push_front((delivery.message, delivery.reply_to));
+                    queue.notify.notify_one();
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn queue_depth(&self, queue: &str) -> Result<u32, BusError> {
+        Ok(self.queue(queue).items.lock().unwrap().len() as u32)
+    }
+
+    async fn publish_result(&self, result: &JobResult) -> Result<(), BusError> {
+        // Abone yoksa sonuç düşer; mandatory yayınlanan fanout exchange gibi hata döner
+        self.results.send(result.clone())
+            .map(|_| ())
+            .map_err(|_| BusError::Rejected("Sonucun abonesi yok".to_string()))
+    }
+
+    async fn subscribe_results(&self) -> Result<Results, BusError> {
+        let mut results = self.results.subscribe();
+        let (tx, rx) = mpsc::channel(RESULTS_CAPACITY);
+
+        tokio::spawn(async move {
+            loop {
+                match results.recv().await {
+                    Ok(result) => {
+                        if tx.send(result).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(skipped, "İş sonuçları atlandı");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Operation, SCHEMA_VERSION};
+
+    fn job(id: &str) -> JobMessage {
+        JobMessage {
+            schema_version: SCHEMA_VERSION,
+            id: id.to_string(),
+            operation: Operation::Encrypt,
+            data: "merhaba".to_string(),
+            subject: "admin".to_string(),
+            callback_url: None,
+            traceparent: Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_consume() {
+        let bus = MemoryBus::new();
+        bus.publish("encrypt_queue", &job("job-1")).await.unwrap();
+        bus.publish("encrypt_queue", &job("job-2")).await.unwrap();
+        assert_eq!(bus.queue_depth("encrypt_queue").await.unwrap(), 2);
+
+        let mut deliveries = bus.consume("encrypt_queue").await.unwrap();
+        let first = deliveries.recv().await.unwrap();
+        assert_eq!(first.message, job("job-1"));
+        assert!(!format!("{:?}", first.message).contains("merhaba"));
+        assert!(first.reply_to.is_none());
+        first.ack().await.unwrap();
+
+        assert_eq!(deliveries.recv().await.unwrap().message.id, "job-2");
+        assert_eq!(bus.queue_depth("decrypt_queue").await.unwrap(), 0);
+    }
+
+    // Yavaş tüketici yalnızca elindeki işi tutar; gerisi kuyrukta kalır ve derinliğe yansır
+    #[tokio::test]
+    async fn test_depth_grows_while_consumer_is_slow() {
+        let bus = MemoryBus::new();
+        let mut deliveries = bus.consume("encrypt_queue").await.unwrap();
+        for i in 0..5 {
+            bus.publish("encrypt_queue", &job(&format!("job-{}", i))).await.unwrap();
+        }
+
+        let in_progress = deliveries.recv().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // Tüketicideki, kanal tamponundaki ve gönderilmeyi bekleyen iş dışındakiler
+        let depth = bus.queue_depth("encrypt_queue").await.unwrap();
+        assert_eq!(depth, 2);
+
+        for i in 5..10 {
+            bus.publish("encrypt_queue", &job(&format!("job-{}", i))).await.unwrap();
+        }
+        assert_eq!(bus.queue_depth("encrypt_queue").await.unwrap(), depth + 5);
+        in_progress.ack().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_limits_unacked_jobs() {
+        let bus = MemoryBus::new().with_prefetch(1);
+        let mut deliveries = bus.consume("encrypt_queue").await.unwrap();
+        bus.publish("encrypt_queue", &job("job-1")).await.unwrap();
+        bus.publish("encrypt_queue", &job("job-2")).await.unwrap();
+
+        let first = deliveries.recv().await.unwrap();
+        assert!(tokio::time::timeout(Duration::from_millis(50), deliveries.recv()).await.is_err());
+        assert_eq!(bus.queue_depth("encrypt_queue").await.unwrap(), 1);
+
+        first.ack().await.unwrap();
+        assert_eq!(deliveries.recv().await.unwrap().message.id, "job-2");
+    }
+
+    #[tokio::test]
+    async fn test_result_without_subscriber_is_rejected() {
+        let bus = MemoryBus::new();
+        let result = JobResult {
+            schema_version: SCHEMA_VERSION,
+            message_id: "job-1".to_string(),
+            subject: "admin".to_string(),
+            success: true,
+            data: None,
+            error: None,
+            code: None,
+        };
+        assert!(matches!(bus.publish_result(&result).await, Err(BusError::Rejected(_))));
+
+        let _results = bus.subscribe_results().await.unwrap();
+        assert_eq!(bus.publish_result(&result).await, Ok(()));
+    }
+
+    #[test]
+    fn test_reply_to_must_be_gateway_queue() {
+        let reply_to = |to: &str| ReplyTo { to: to.to_string(), correlation_id: "job-1".to_string() };
+
+        assert!(reply_to(&reply_address()).is_gateway());
+        assert!(!reply_to(REPLY_PREFIX).is_gateway());
+        assert!(!reply_to("encrypt_queue").is_gateway());
+        assert!(!reply_to("amq.gen-saldirgan").is_gateway());
+    }
+
+    #[tokio::test]
+    async fn test_request_reply_and_results() {
+        let bus = Arc::new(MemoryBus::new());
+        let mut results = bus.subscribe_results().await.unwrap();
+        let mut deliveries = bus.consume("encrypt_queue").await.unwrap();
+
+        let worker = bus.clone();
+        tokio::spawn(async move {
+            let delivery = deliveries.recv().await.unwrap();
+            let reply_to = delivery.reply_to.clone().unwrap();
+            assert!(reply_to.is_gateway());
+            worker.publish_result(&JobResult {
+                schema_version: SCHEMA_VERSION,
+                message_id: delivery.message.id.clone(),
+                subject: delivery.message.subject.clone(),
+                success: true,
+                data: Some("sonuç".to_string()),
+                error: None,
+                code: None,
+            }).await.unwrap();
+            worker.reply(&reply_to, b"yanit").await.unwrap();
+            delivery.ack().await.unwrap();
+        });
+
+        let reply = bus.request("encrypt_queue", &job("job-1"), Duration::from_secs(5)).await.unwrap();
+        assert_eq!(reply, b"yanit");
+        assert_eq!(results.recv().await.unwrap().message_id, "job-1");
+
+        assert_eq!(
+            bus.request("decrypt_queue", &job("job-2"), Duration::from_millis(10)).await,
+            Err(BusError::Timeout(Duration::from_millis(10)))
+        );
+    }
+}
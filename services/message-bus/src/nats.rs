@@ -0,0 +1,376 @@
+use async_nats::jetstream::{self, consumer::pull, consumer::AckPolicy, stream::RetentionPolicy, AckKind};
+use async_nats::{Client, ConnectOptions, HeaderMap};
+use async_trait::async_trait;
+use futures_util::{StreamExt, TryStreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex, OnceCell};
+use crate::bus::*;
+use crate::DEFAULT_PREFETCH;
+
+// Tüm iş kuyrukları tek bir WorkQueue stream'inde, kuyruk başına bir subject ile tutulur
+const JOBS_STREAM: &str = "CRYPT_JOBS";
+const JOBS_SUBJECT_PREFIX: &str = "crypt.jobs";
+const JOB_RESULTS_SUBJECT: &str = "crypt.job_results";
+const REPLY_TO_HEADER: &str = "Crypt-Reply-To";
+const CORRELATION_ID_HEADER: &str = "Crypt-Correlation-Id";
+// Bu süre içinde ack edilmeyen iş yeniden teslim edilir. crypt-processor webhook'lu işleri
+// teslimat bitince ack eder; varsayılan denemelerin toplamı (~1.5 dk) bu sürenin altında kalmalı
+const ACK_WAIT: Duration = Duration::from_secs(300);
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+type PendingReplies = Arc<Mutex<HashMap<String, oneshot::Sender<Vec<u8>>>>>;
+
+fn job_subject(queue: &str) -> String {
+    format!("{}.{}", JOBS_SUBJECT_PREFIX, queue)
+}
+
+fn consumer_name(queue: &str) -> String {
+    format!("crypt-processor-{}", queue)
+}
+
+fn transport<E: std::fmt::Display>(e: E) -> BusError {
+    BusError::Transport(e.to_string())
+}
+
+// NATS JetStream üzerinden MessageBus. İşler kalıcı stream'e yazılır ve kuyruk
+// başına durable pull consumer ile açık ack ile tüketilir; sonuçlar ve senkron
+// yanıtlar çekirdek NATS subject'leri üzerinden gider. Yeniden bağlanmayı
+// async-nats istemcisi kendisi yapar
+pub struct NatsBus {
+    client: Client,
+    jetstream: jetstream::Context,
+    stream: OnceCell<jetstream::stream::Stream>,
+    inbox: OnceCell<String>,
+    pending: PendingReplies,
+    prefetch: u16,
+}
+
+impl NatsBus {
+    pub async fn connect(url: &str) -> Result<Self, BusError> {
+        let client = ConnectOptions::new()
+            .retry_on_initial_connect()
+            .connect(url)
+            .await
+            .map_err(transport)?;
+
+        Ok(Self {
+            jetstream: jetstream::new(client.clone()),
+            client,
+            stream: OnceCell::new(),
+            inbox: OnceCell::new(),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            prefetch: DEFAULT_PREFETCH,
+        })
+    }
+
+    // consume ile açılan tüketicilerin ack edilmemiş iş sınırı (max_ack_pending)
+    pub fn with_prefetch(mut self, prefetch: u16) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    async fn stream(&self) -> Result<&jetstream::stream::Stream, BusError> {
+        self.stream.get_or_try_init(|| async {
+            self.jetstream.get_or_create_stream(jetstream::stream::Config {
+                name: JOBS_STREAM.to_string(),
+                subjects: vec![format!("{}.>", JOBS_SUBJECT_PREFIX)],
+                retention: RetentionPolicy::WorkQueue,
+                ..Default::default()
+            }).await.map_err(transport)
+        }).await
+    }
+
+    // Bu örneğe özel yanıt subject'i; ilk senkron istekte açılır
+    async fn inbox(&self) -> Result<&String, BusError> {
+        self.inbox.get_or_try_init(|| async {
+            let inbox = reply_address();
+            let mut subscriber = self.client.subscribe(inbox.clone()).await.map_err(transport)?;
+            let pending = self.pending.clone();
+
+            tokio::spawn(async move {
+                while let Some(message) = subscriber.next().await {
+                    let Some(correlation_id) = message.headers.as_ref()
+                        .and_then(|headers| headers.get(CORRELATION_ID_HEADER))
+                        .map(|value| value.to_string()) else {
+                        continue;
+                    };
+
+                    // Zaman aşımına uğramış isteklerin geç yanıtları sessizce düşürülür
+                    if let Some(tx) = pending.lock().await.remove(&correlation_id) {
+                        let _ = tx.send(message.payload.to_vec());
+                    }
+                }
+            });
+
+            Ok(inbox)
+        }).await
+    }
+
+    // PubAck alınınca döner; stream mesajı diske yazmıştır
+    async fn publish_job(&self, queue: &str, message: &JobMessage, mut headers: HeaderMap) -> Result<(), BusError> {
+        self.stream().await?;
+        if let Some(traceparent) = &message.traceparent {
+            headers.insert(TRACEPARENT_HEADER, traceparent.as_str());
+        }
+        let payload = serde_json::to_vec(message).map_err(transport)?;
+
+        self.jetstream
+            .publish_with_headers(job_subject(queue), headers, payload.into())
+            .await
+            .map_err(transport)?
+            .await
+            .map_err(|e| BusError::Rejected(e.to_string()))?;
+        Ok(())
+    }
+}
+
+async fn forward_jobs(messages: &mut pull::Stream, tx: &mpsc::Sender<Delivery>) -> bool {
+    while let Some(message) = messages.next().await {
+        let message = match message {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!(error = %e, "JetStream teslimat hatası");
+                continue;
+            }
+        };
+
+        let mut job = match serde_json::from_slice::<JobMessage>(&message.payload) {
+            Ok(job) => job,
+            Err(e) => {
+                tracing::warn!(error = %e, "Geçersiz iş mesajı reddedildi");
+                let _ = message.ack_with(AckKind::Term).await;
+                continue;
+            }
+        };
+
+        let header = |name: &str| message.headers.as_ref()
+            .and_then(|headers| headers.get(name))
+            .map(|value| value.to_string());
+        job.traceparent = header(TRACEPARENT_HEADER);
+        let reply_to = header(REPLY_TO_HEADER)
+            .zip(header(CORRELATION_ID_HEADER))
+            .map(|(to, correlation_id)| ReplyTo { to, correlation_id });
+
+        if let Err(mpsc::error::SendError(delivery)) = tx.send(Delivery {
+            message: job,
+            reply_to,
+            acker: Acker::Nats(Box::new(message)),
+        }).await {
+            if let Acker::Nats(message) = delivery.acker {
+                let _ = message.ack_with(AckKind::Nak(None)).await;
+            }
+            return false;
+        }
+    }
+    true
+}
+
+#[async_trait]
+impl MessageBus for NatsBus {
+    fn is_ready(&self) -> bool {
+        self.client.connection_state() == async_nats::connection::State::Connected
+    }
+
+    async fn publish(&self, queue: &str, message: &JobMessage) -> Result<(), BusError> {
+        self.publish_job(queue, message, HeaderMap::new()).await
+    }
+
+    async fn request(&self, queue: &str, message: &JobMessage, timeout: Duration) -> Result<Vec<u8>, BusError> {
+        let inbox = self.inbox().await?.clone();
+
+        // Yanıt yayından önce kaydedilir, aksi halde hızlı bir yanıt kaçabilir
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(message.id.clone(), tx);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(REPLY_TO_HEADER, inbox.as_str());
+        headers.insert(CORRELATION_ID_HEADER, message.id.as_str());
+
+        if let Err(e) = self.publish_job(queue, message, headers).await {
+            self.pending.lock().await.remove(&message.id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(BusError::Transport("Yanıt kanalı kapandı".to_string())),
+            Err(_) => {
+                self.pending.lock().await.remove(&message.id);
+                Err(BusError::Timeout(timeout))
+            }
+        }
+    }
+
+    async fn reply(&self, reply_to: &ReplyTo, payload: &[u8]) -> Result<(), BusError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CORRELATION_ID_HEADER, reply_to.correlation_id.as_str());
+
+        self.client
+            .publish_with_headers(reply_to.to.clone(), headers, payload.to_vec().into())
+            .await
+            .map_err(transport)
+    }
+
+    async fn consume(&self, queue: &str) -> Result<Deliveries, BusError> {
+        let name = consumer_name(queue);
+        let consumer: jetstream::consumer::Consumer<pull::Config> = self.stream().await?
+            .get_or_create_consumer(&name, pull::Config {
+                durable_name: Some(name.clone()),
+                ack_policy: AckPolicy::Explicit,
+                ack_wait: ACK_WAIT,
+                max_ack_pending: self.prefetch as i64,
+                filter_subject: job_subject(queue),
+                ..Default::default()
+            })
+            .await
+            .map_err(transport)?;
+
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move {
+            while !tx.is_closed() {
+                match consumer.messages().await {
+                    Ok(mut messages) => {
+                        if !forward_jobs(&mut messages, &tx).await {
+                            break;
+                        }
+                    }
+                    Err(e) => tracing::warn!(error = %e, "JetStream tüketicisi başlatılamadı"),
+                }
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn queue_depth(&self, queue: &str) -> Result<u32, BusError> {
+        if !self.is_ready() {
+            return Err(BusError::NotConnected);
+        }
+
+        // WorkQueue stream'inde ack edilen mesajlar silinir, kalanlar bekleyen işlerdir
+        let subject = job_subject(queue);
+        let mut subjects = self.stream().await?
+            .info_with_subjects(&subject)
+            .await
+            .map_err(transport)?;
+
+        let mut depth = 0;
+        while let Some((name, count)) = subjects.try_next().await.map_err(transport)? {
+            if name == subject {
+                depth += count;
+            }
+        }
+        Ok(depth as u32)
+    }
+
+    async fn publish_result(&self, result: &JobResult) -> Result<(), BusError> {
+        let payload = serde_json::to_vec(result).map_err(transport)?;
+        self.client
+            .publish(JOB_RESULTS_SUBJECT, payload.into())
+            .await
+            .map_err(transport)
+    }
+
+    async fn subscribe_results(&self) -> Result<Results, BusError> {
+        let mut subscriber = self.client.subscribe(JOB_RESULTS_SUBJECT).await.map_err(transport)?;
+        let (tx, rx) = mpsc::channel(1024);
+
+        tokio::spawn(async move {
+            while let Some(message) = subscriber.next().await {
+                match serde_json::from_slice::<JobResult>(&message.payload) {
+                    Ok(result) => {
+                        if tx.send(result).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Geçersiz iş sonucu mesajı"),
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
+
+// Yerel bir nats-server gerektirir: nats-server -js
+// NATS_URL=nats://localhost:4222 cargo test -p message-bus -- --ignored
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Operation, SCHEMA_VERSION};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    // Testler aynı sunucuda tekrar çalıştırılabilsin diye kuyruk adları benzersizdir
+    fn unique(prefix: &str) -> String {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        format!("{}_{}", prefix, nanos)
+    }
+
+    async fn bus() -> NatsBus {
+        let url = std::env::var("NATS_URL").unwrap_or("nats://localhost:4222".to_string());
+        NatsBus::connect(&url).await.unwrap()
+    }
+
+    fn job(id: &str) -> JobMessage {
+        JobMessage {
+            schema_version: SCHEMA_VERSION,
+            id: id.to_string(),
+            operation: Operation::Encrypt,
+            data: "merhaba".to_string(),
+            subject: "admin".to_string(),
+            callback_url: None,
+            traceparent: Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_jetstream_publish_consume_ack() {
+        let bus = bus().await;
+        let queue = unique("test_queue");
+
+        bus.publish(&queue, &job("job-1")).await.unwrap();
+        assert_eq!(bus.queue_depth(&queue).await.unwrap(), 1);
+
+        let mut deliveries = bus.consume(&queue).await.unwrap();
+        let delivery = deliveries.recv().await.unwrap();
+        assert_eq!(delivery.message, job("job-1"));
+        delivery.ack().await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(bus.queue_depth(&queue).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    #[ignore]
+    async fn test_jetstream_request_reply_and_results() {
+        let bus = Arc::new(bus().await);
+        let queue = unique("test_queue");
+        let mut results = bus.subscribe_results().await.unwrap();
+        let mut deliveries = bus.consume(&queue).await.unwrap();
+
+        let worker = bus.clone();
+        tokio::spawn(async move {
+            let delivery = deliveries.recv().await.unwrap();
+            worker.publish_result(&JobResult {
+                schema_version: SCHEMA_VERSION,
+                message_id: delivery.message.id.clone(),
+                subject: delivery.message.subject.clone(),
+                success: true,
+                data: None,
+                error: None,
+                code: None,
+            }).await.unwrap();
+            worker.reply(delivery.reply_to.as_ref().unwrap(), b"yanit").await.unwrap();
+            delivery.ack().await.unwrap();
+        });
+
+        let id = unique("job");
+        let reply = bus.request(&queue, &job(&id), Duration::from_secs(5)).await.unwrap();
+        assert_eq!(reply, b"yanit");
+        assert_eq!(results.recv().await.unwrap().message_id, id);
+    }
+}
@@ -0,0 +1,243 @@
+use lapin::{Channel, Connection, ConnectionProperties};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::{oneshot, watch};
+
+pub type Closed = Pin<Box<dyn Future<Output = String> + Send>>;
+
+// Kurulan bir bağlantı ve bağlantı koptuğunda nedeni ile tamamlanan future
+pub struct Session<T> {
+    pub handle: T,
+    pub closed: Closed,
+}
+
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max, current: initial }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_secs(30))
+    }
+}
+
+// Bağlantıyı arka planda kurar ve koptuğunda üstel bekleme ile yeniden kurar.
+// Bağlantı yokken current() None, is_ready() false döner
+pub struct Supervisor<T> {
+    current: Arc<RwLock<Option<Arc<T>>>>,
+    ready: watch::Receiver<bool>,
+}
+
+impl<T> Clone for Supervisor<T> {
+    fn clone(&self) -> Self {
+        Self { current: self.current.clone(), ready: self.ready.clone() }
+    }
+}
+
+impl<T: Send + Sync + 'static> Supervisor<T> {
+    pub fn spawn<F, Fut>(mut backoff: Backoff, connect: F) -> Self
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Session<T>, String>> + Send,
+    {
+        let current = Arc::new(RwLock::new(None));
+        let (ready_tx, ready) = watch::channel(false);
+        let slot = current.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match connect().await {
+                    Ok(session) => {
+                        *slot.write().unwrap() = Some(Arc::new(session.handle));
+                        let _ = ready_tx.send(true);
+                        backoff.reset();
+                        tracing::info!("Mesaj kuyruğu bağlantısı kuruldu");
+
+                        let reason = session.closed.await;
+                        *slot.write().unwrap() = None;
+                        let _ = ready_tx.send(false);
+                        tracing::warn!(%reason, "Mesaj kuyruğu bağlantısı koptu");
+                    }
+                    Err(e) => tracing::warn!(error = %e, "Mesaj kuyruğuna bağlanılamadı"),
+                }
+
+                if ready_tx.is_closed() {
+                    break;
+                }
+                let delay = backoff.next_delay();
+                tracing::info!(delay_ms = delay.as_millis() as u64, "Yeniden bağlanılacak");
+                tokio::time::sleep(delay).await;
+            }
+        });
+
+        Self { current, ready }
+    }
+
+    pub fn current(&self) -> Option<Arc<T>> {
+        self.current.read().unwrap().clone()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        *self.ready.borrow()
+    }
+
+    pub async fn wait_ready(&self, ready: bool) {
+        let mut receiver = self.ready.clone();
+        let _ = receiver.wait_for(|value| *value == ready).await;
+    }
+}
+
+#[derive(Clone)]
+pub struct AmqpSession {
+    pub connection: Arc<Connection>,
+    pub channel: Arc<Channel>,
+}
+
+// Bağlantı düştüğünde kuyruk tanımları ve tüketiciler setup ile yeniden kurulur
+pub fn supervise_amqp<F, Fut>(url: String, backoff: Backoff, setup: F) -> Supervisor<AmqpSession>
+where
+    F: Fn(AmqpSession) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), lapin::Error>> + Send,
+{
+    let setup = Arc::new(setup);
+    Supervisor::spawn(backoff, move || {
+        let url = url.clone();
+        let setup = setup.clone();
+        async move { connect_amqp(&url, &*setup).await }
+    })
+}
+
+async fn connect_amqp<F, Fut>(url: &str, setup: &F) -> Result<Session<AmqpSession>, String>
+where
+    F: Fn(AmqpSession) -> Fut,
+    Fut: Future<Output = Result<(), lapin::Error>>,
+{
+    let connection = Connection::connect(url, ConnectionProperties::default())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let (error_tx, error_rx) = oneshot::channel();
+    let error_tx = std::sync::Mutex::new(Some(error_tx));
+    connection.on_error(move |e| {
+        if let Some(tx) = error_tx.lock().unwrap().take() {
+            let _ = tx.send(e.to_string());
+        }
+    });
+
+    let channel = connection.create_channel().await.map_err(|e| e.to_string())?;
+    let session = AmqpSession {
+        connection: Arc::new(connection),
+        channel: Arc::new(channel),
+    };
+    setup(session.clone()).await.map_err(|e| e.to_string())?;
+
+    // on_error her kapanışta çağrılmayabilir (ör. broker'ın düzgün kapatması),
+    // bu yüzden bağlantı durumu da periyodik olarak kontrol edilir
+    let connection = session.connection.clone();
+    let closed = Box::pin(async move {
+        let watch_status = async {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            while connection.status().connected() {
+                interval.tick().await;
+            }
+            "bağlantı kapandı".to_string()
+        };
+
+        tokio::select! {
+            Ok(reason) = error_rx => reason,
+            reason = watch_status => reason,
+        }
+    });
+
+    Ok(Session { handle: session, closed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::{Mutex, Notify};
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(Duration::from_millis(100), Duration::from_millis(350));
+        let delays: Vec<_> = (0..4).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, [100, 200, 350, 350]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay().as_millis(), 100);
+    }
+
+    // Broker yerine geçen bağlantı: ilk iki deneme başarısız olur, sonrakiler
+    // testin kontrol ettiği bir kapanma sinyali ile döner. Kopmadan sonraki deneme
+    // test izin verene kadar bekler, böylece hazır değil durumu gözlenebilir
+    #[tokio::test]
+    async fn test_reconnects_after_failures_and_disconnects() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let disconnect: Arc<Mutex<Option<oneshot::Sender<()>>>> = Arc::new(Mutex::new(None));
+        let allow_reconnect = Arc::new(Notify::new());
+
+        let supervisor = Supervisor::spawn(
+            Backoff::new(Duration::from_millis(1), Duration::from_millis(5)),
+            {
+                let attempts = attempts.clone();
+                let disconnect = disconnect.clone();
+                let allow_reconnect = allow_reconnect.clone();
+                move || {
+                    let attempts = attempts.clone();
+                    let disconnect = disconnect.clone();
+                    let allow_reconnect = allow_reconnect.clone();
+                    async move {
+                        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                        if attempt <= 2 {
+                            return Err("bağlantı reddedildi".to_string());
+                        }
+                        if attempt > 3 {
+                            allow_reconnect.notified().await;
+                        }
+                        let (tx, rx) = oneshot::channel();
+                        *disconnect.lock().await = Some(tx);
+                        let closed: Closed = Box::pin(async move {
+                            let _ = rx.await;
+                            "broker yeniden başladı".to_string()
+                        });
+                        Ok(Session { handle: attempt, closed })
+                    }
+                }
+            },
+        );
+
+        supervisor.wait_ready(true).await;
+        assert!(supervisor.is_ready());
+        assert_eq!(*supervisor.current().unwrap(), 3);
+
+        disconnect.lock().await.take().unwrap().send(()).unwrap();
+        supervisor.wait_ready(false).await;
+        assert!(supervisor.current().is_none());
+
+        allow_reconnect.notify_one();
+        supervisor.wait_ready(true).await;
+        assert_eq!(*supervisor.current().unwrap(), 4);
+    }
+}
@@ -0,0 +1,65 @@
+use lapin::{
+    options::{ExchangeDeclareOptions, QueueDeclareOptions},
+    types::FieldTable,
+    BasicProperties, Channel, ExchangeKind,
+};
+use protocol::Operation;
+
+pub const ENCRYPT_QUEUE: &str = "encrypt_queue";
+pub const DECRYPT_QUEUE: &str = "decrypt_queue";
+pub const WORK_QUEUES: [&str; 2] = [ENCRYPT_QUEUE, DECRYPT_QUEUE];
+
+// Açık metin üreten işler encrypt, açık metne dönenler decrypt kuyruğuna gider
+pub fn work_queue(operation: Operation) -> &'static str {
+    match operation {
+        Operation::Encrypt | Operation::EncryptFields | Operation::Tokenize | Operation::Unknown => ENCRYPT_QUEUE,
+        Operation::Decrypt | Operation::DecryptFields | Operation::Detokenize | Operation::DecryptLegacy => DECRYPT_QUEUE,
+    }
+}
+
+// crypt-processor her işin sonucunu bu fanout exchange'e yazar; her crypt-gate
+// örneği kendi geçici kuyruğu ile dinler
+pub const JOB_RESULTS_EXCHANGE: &str = "job_results";
+
+// AMQP delivery_mode: 2 = kalıcı (diske yazılır)
+const PERSISTENT: u8 = 2;
+
+// İş kuyrukları broker yeniden başladığında korunur. Tanım iki serviste de aynı
+// olmalıdır, aksi halde broker PRECONDITION_FAILED ile kanalı kapatır
+pub fn work_queue_options() -> QueueDeclareOptions {
+    QueueDeclareOptions { durable: true, ..QueueDeclareOptions::default() }
+}
+
+pub async fn declare_topology(channel: &Channel) -> Result<(), lapin::Error> {
+    for queue in WORK_QUEUES {
+        channel.queue_declare(queue, work_queue_options(), FieldTable::default()).await?;
+    }
+
+    // Sonuçlar yalnızca bağlı crypt-gate örnekleri için anlamlıdır, kalıcı değildir
+    channel.exchange_declare(
+        JOB_RESULTS_EXCHANGE,
+        ExchangeKind::Fanout,
+        ExchangeDeclareOptions::default(),
+        FieldTable::default(),
+    ).await?;
+
+    Ok(())
+}
+
+// İş kuyruklarına yazılan mesajlar için
+pub fn persistent_properties() -> BasicProperties {
+    BasicProperties::default().with_delivery_mode(PERSISTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_work_queues_are_durable() {
+        let options = work_queue_options();
+        assert!(options.durable);
+        assert!(!options.exclusive && !options.auto_delete && !options.passive);
+        assert_eq!(persistent_properties().delivery_mode(), &Some(PERSISTENT));
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// crypt-processor'ın başarısız iş sonuçlarında ve HTTP hata gövdelerinde kullandığı kodlar.
+// Gateway'in REST hata kodları (VALIDATION_ERROR vb.) crypt-gate'te tanımlıdır
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    CryptError,
+    FormatError,
+    QueueError,
+    WebsocketError,
+    SerializationError,
+    Unauthorized,
+    UnsupportedOperation,
+    UnsupportedSchemaVersion,
+    // Daha yeni bir processor'ın eklediği kod
+    #[serde(other)]
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::CryptError => "CRYPT_ERROR",
+            ErrorCode::FormatError => "FORMAT_ERROR",
+            ErrorCode::QueueError => "QUEUE_ERROR",
+            ErrorCode::WebsocketError => "WEBSOCKET_ERROR",
+            ErrorCode::SerializationError => "SERIALIZATION_ERROR",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::UnsupportedOperation => "UNSUPPORTED_OPERATION",
+            ErrorCode::UnsupportedSchemaVersion => "UNSUPPORTED_SCHEMA_VERSION",
+            ErrorCode::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_names() {
+        for code in [ErrorCode::CryptError, ErrorCode::WebsocketError, ErrorCode::UnsupportedSchemaVersion] {
+            let json = serde_json::to_string(&code).unwrap();
+            assert_eq!(json, format!("\"{}\"", code.as_str()));
+            assert_eq!(serde_json::from_str::<ErrorCode>(&json).unwrap(), code);
+        }
+
+        assert_eq!(serde_json::from_str::<ErrorCode>("\"KEY_REVOKED\"").unwrap(), ErrorCode::Unknown);
+    }
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::{ErrorCode, Operation};
+
+// Sürüm alanından önceki göndericilerin mesajları
+fn legacy_schema_version() -> u32 {
+    1
+}
+
+// crypt-gate'in kuyruğa yazdığı iş (iş zarfı)
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct JobMessage {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    pub id: String,
+    pub operation: Operation,
+    pub data: String,
+    #[serde(default)]
+    pub subject: String,    // işi gönderen kullanıcı (JWT sub)
+    // Sonucun POST edileceği adres; crypt-gate izin listesiyle doğrulamıştır
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback_url: Option<String>,
+    // W3C traceparent; gövdede değil, taşıyıcının mesaj başlıklarında gider
+    #[serde(skip)]
+    pub traceparent: Option<String>,
+}
+
+// data düz metin veya anahtar içerebilir; Debug çıktısı loglara düşebileceği için gizlenir
+impl fmt::Debug for JobMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JobMessage")
+            .field("schema_version", &self.schema_version)
+            .field("id", &self.id)
+            .field("operation", &self.operation)
+            .field("data", &format_args!("<{} bayt>", self.data.len()))
+            .field("subject", &self.subject)
+            .field("callback_url", &self.callback_url)
+            .field("traceparent", &self.traceparent)
+            .finish()
+    }
+}
+
+// crypt-processor'ın her iş için yayınladığı sonuç (sonuç zarfı); crypt-gate'in iş durumu deposuna gider
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct JobResult {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    pub message_id: String,
+    pub subject: String,
+    pub success: bool,
+    pub data: Option<String>,
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+}
+
+impl fmt::Debug for JobResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JobResult")
+            .field("schema_version", &self.schema_version)
+            .field("message_id", &self.message_id)
+            .field("subject", &self.subject)
+            .field("success", &self.success)
+            .field("data", &self.data.as_ref().map(|data| format!("<{} bayt>", data.len())))
+            .field("error", &self.error)
+            .field("code", &self.code)
+            .finish()
+    }
+}
+
+// İstemciye giden sonuç çerçevesi: WebSocket text mesajı, SSE data satırı, webhook
+// gövdesi ve sync modda crypt-gate'in aynen ilettiği yanıt.
+// Debug türetilmez: data düz metin içerebilir ve loglanmamalıdır
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JobReply {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    pub success: bool,
+    pub message_id: String,
+    pub data: Option<String>,
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    // İstemcinin sonucu gateway isteğiyle eşleştirebilmesi için
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceparent: Option<String>,
+}
+
+impl JobReply {
+    pub fn to_result(&self, subject: &str) -> JobResult {
+        JobResult {
+            schema_version: self.schema_version,
+            message_id: self.message_id.clone(),
+            subject: subject.to_string(),
+            success: self.success,
+            data: self.data.clone(),
+            error: self.error.clone(),
+            code: self.code,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde::de::DeserializeOwned;
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq>(value: &T) -> String {
+        let json = serde_json::to_string(value).unwrap();
+        assert!(serde_json::from_str::<T>(&json).unwrap() == *value, "{}", json);
+        json
+    }
+
+    #[test]
+    fn test_job_message_round_trip() {
+        let mut message = JobMessage {
+            schema_version: SCHEMA_VERSION,
+            id: "job-1".to_string(),
+            operation: Operation::EncryptFields,
+            data: "{}".to_string(),
+            subject: "alice".to_string(),
+            callback_url: Some("https://hooks.example.com/crypt".to_string()),
+            traceparent: None,
+        };
+        let json = round_trip(&message);
+        assert!(json.contains(r#""schema_version":1"#));
+        assert!(json.contains(r#""operation":"encrypt_fields""#));
+
+        // traceparent gövdeye yazılmaz
+        message.traceparent = Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string());
+        assert!(!serde_json::to_string(&message).unwrap().contains("traceparent"));
+    }
+
+    #[test]
+    fn test_result_and_reply_round_trip() {
+        let reply = JobReply {
+            schema_version: SCHEMA_VERSION,
+            success: false,
+            message_id: "job-1".to_string(),
+            data: None,
+            error: Some("Desteklenmeyen operasyon: unknown".to_string()),
+            code: Some(ErrorCode::UnsupportedOperation),
+            traceparent: None,
+        };
+        let json = round_trip(&reply);
+        assert!(json.contains(r#""code":"UNSUPPORTED_OPERATION""#));
+        assert!(!json.contains("traceparent"));
+
+        let result = reply.to_result("alice");
+        round_trip(&result);
+        assert_eq!(result.code, Some(ErrorCode::UnsupportedOperation));
+    }
+
+    // Sürüm alanı ve code eklenmeden önceki göndericiler
+    #[test]
+    fn test_reads_unversioned_messages() {
+        let message: JobMessage = serde_json::from_str(
+            r#"{"id":"job-1","operation":"encrypt","data":"merhaba","subject":"alice"}"#
+        ).unwrap();
+        assert_eq!(message.schema_version, 1);
+
+        let result: JobResult = serde_json::from_str(
+            r#"{"message_id":"job-1","subject":"alice","success":true,"data":"x","error":null}"#
+        ).unwrap();
+        assert_eq!(result.schema_version, 1);
+        assert_eq!(result.code, None);
+
+        let reply: JobReply = serde_json::from_str(
+            r#"{"success":true,"message_id":"job-1","data":"x","error":null,"extra":1}"#
+        ).unwrap();
+        assert_eq!(reply.schema_version, 1);
+    }
+}
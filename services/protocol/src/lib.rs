@@ -0,0 +1,18 @@
+// crypt-gate ile crypt-processor arasındaki mesaj biçimleri. İki servis ayrı ayrı
+// dağıtılabildiği için her zarf schema_version taşır
+mod error;
+mod job;
+mod operation;
+
+pub use error::ErrorCode;
+pub use job::{JobMessage, JobReply, JobResult};
+pub use operation::Operation;
+
+// Yalnızca geriye uyumsuz değişiklikte artırılır. Yeni isteğe bağlı alanlar ve enum
+// değerleri sürüm gerektirmez: alıcı tanımadığı alanı yok sayar, değeri Unknown okur
+pub const SCHEMA_VERSION: u32 = 1;
+
+// Alıcı kendi sürümünden yeni zarfın anlamını bilemez
+pub fn is_supported(schema_version: u32) -> bool {
+    schema_version <= SCHEMA_VERSION
+}
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// crypt-gate'in kuyruğa yazdığı iş türü; crypt-processor her biri için bir
+// OperationHandler kaydeder. Yeni operasyon önce buraya, kuyruğu message_bus::work_queue'ya eklenir
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Encrypt,
+    Decrypt,
+    EncryptFields,
+    DecryptFields,
+    Tokenize,
+    Detokenize,
+    DecryptLegacy,
+    // Bu sürümün tanımadığı operasyon (daha yeni bir gateway'den); processor hata sonucu döner
+    #[serde(other)]
+    Unknown,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 7] = [
+        Operation::Encrypt,
+        Operation::Decrypt,
+        Operation::EncryptFields,
+        Operation::DecryptFields,
+        Operation::Tokenize,
+        Operation::Detokenize,
+        Operation::DecryptLegacy,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Encrypt => "encrypt",
+            Operation::Decrypt => "decrypt",
+            Operation::EncryptFields => "encrypt_fields",
+            Operation::DecryptFields => "decrypt_fields",
+            Operation::Tokenize => "tokenize",
+            Operation::Detokenize => "detokenize",
+            Operation::DecryptLegacy => "decrypt_legacy",
+            Operation::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_names() {
+        for operation in Operation::ALL {
+            let json = serde_json::to_string(&operation).unwrap();
+            assert_eq!(json, format!("\"{}\"", operation.as_str()));
+            assert_eq!(serde_json::from_str::<Operation>(&json).unwrap(), operation);
+        }
+
+        assert_eq!(serde_json::from_str::<Operation>("\"rewrap\"").unwrap(), Operation::Unknown);
+    }
+}
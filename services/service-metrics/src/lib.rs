@@ -0,0 +1,113 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    Error, HttpResponse,
+};
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, Encoder, HistogramVec, IntCounterVec, TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+// Tüm metrikler prometheus'un varsayılan registry'sine kaydedilir ve /metrics
+// ucunda birlikte yayınlanır
+
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "http_requests_total",
+        "Rota, metot ve durum koduna göre HTTP istek sayısı",
+        &["route", "method", "status"]
+    ).unwrap()
+});
+
+pub static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "http_request_duration_seconds",
+        "Rota ve metoda göre HTTP istek süresi",
+        &["route", "method"]
+    ).unwrap()
+});
+
+pub static JOBS_PUBLISHED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("jobs_published_total", "Kuyruğa yazılan iş sayısı", &["queue"]).unwrap()
+});
+
+pub static JOBS_CONSUMED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("jobs_consumed_total", "Kuyruktan alınan iş sayısı", &["queue"]).unwrap()
+});
+
+pub static JOBS_ACKED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!("jobs_acked_total", "İşlenip ack edilen iş sayısı", &["queue"]).unwrap()
+});
+
+// Eşleşmeyen yollar tek etikette toplanır; aksi halde her rastgele URL yeni bir seri açar
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+// İstek sayısı ve süresini rota şablonu (/jobs/{message_id}) bazında kaydeder
+pub async fn track_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let route = req.match_pattern().unwrap_or(UNMATCHED_ROUTE.to_string());
+    let method = req.method().to_string();
+    let started = Instant::now();
+
+    let result = next.call(req).await;
+
+    let status = match &result {
+        Ok(response) => response.status(),
+        Err(e) => e.as_response_error().status_code(),
+    };
+    HTTP_REQUESTS.with_label_values(&[route.as_str(), method.as_str(), status.as_str()]).inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&[route.as_str(), method.as_str()])
+        .observe(started.elapsed().as_secs_f64());
+
+    result
+}
+
+// Prometheus metin formatında tüm metrikler
+pub fn render() -> HttpResponse {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+
+    match encoder.encode(&prometheus::gather(), &mut buffer) {
+        Ok(()) => HttpResponse::Ok().content_type(encoder.format_type()).body(buffer),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+#[actix_web::get("/metrics")]
+pub async fn metrics() -> HttpResponse {
+    render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{middleware::from_fn, test, web, App};
+
+    #[actix_web::test]
+    async fn test_tracks_route_template() {
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(track_requests))
+                .route("/jobs/{id}", web::get().to(HttpResponse::Ok))
+                .service(metrics)
+        ).await;
+
+        for id in ["a", "b"] {
+            let request = test::TestRequest::get().uri(&format!("/jobs/{}", id)).to_request();
+            test::call_service(&app, request).await;
+        }
+        let request = test::TestRequest::get().uri("/yok").to_request();
+        test::call_service(&app, request).await;
+
+        let request = test::TestRequest::get().uri("/metrics").to_request();
+        let body = String::from_utf8(test::call_and_read_body(&app, request).await.to_vec()).unwrap();
+        assert!(body.contains(r#"http_requests_total{method="GET",route="/jobs/{id}",status="200"} 2"#));
+        assert!(body.contains(r#"http_requests_total{method="GET",route="unmatched",status="404"} 1"#));
+        assert!(body.contains("http_request_duration_seconds_bucket"));
+    }
+}
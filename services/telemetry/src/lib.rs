@@ -0,0 +1,200 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    Error, FromRequest, HttpMessage, HttpRequest,
+};
+use std::env;
+use std::future::{ready, Ready};
+use std::time::Instant;
+use tracing::Instrument;
+use tracing_subscriber::EnvFilter;
+
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+// Log seviyesi RUST_LOG ile (varsayılan info), biçim LOG_FORMAT ile seçilir:
+// json (varsayılan) veya geliştirme için text
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    // Testlerde birden fazla çağrılabilir; ilk kurulan subscriber geçerli kalır
+    let _ = match env::var("LOG_FORMAT").as_deref() {
+        Ok("text") => builder.try_init(),
+        _ => builder.json().with_current_span(true).with_span_list(false).try_init(),
+    };
+}
+
+// W3C trace context (traceparent: 00-<trace-id>-<span-id>-<flags>). Aynı trace_id
+// HTTP isteğinden kuyruk mesajına ve WebSocket sonucuna kadar taşınır
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceContext {
+    pub trace_id: String,
+    pub span_id: String,
+    pub sampled: bool,
+}
+
+fn random_hex(bytes: usize) -> String {
+    loop {
+        let id: String = (0..bytes).map(|_| format!("{:02x}", rand::random::<u8>())).collect();
+        // Tamamen sıfır kimlik spesifikasyona göre geçersizdir
+        if id.bytes().any(|b| b != b'0') {
+            return id;
+        }
+    }
+}
+
+fn is_valid_id(id: &str, len: usize) -> bool {
+    id.len() == len
+        && id.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+        && id.bytes().any(|b| b != b'0')
+}
+
+impl TraceContext {
+    pub fn new_root() -> Self {
+        Self { trace_id: random_hex(16), span_id: random_hex(8), sampled: true }
+    }
+
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parts = header.trim().split('-');
+        let (version, trace_id, span_id, flags) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+
+        // Bilinmeyen sürümlerde fazladan alan olabilir, 00'da olamaz
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if !is_valid_id(trace_id, 32) || !is_valid_id(span_id, 16) || flags.len() != 2 {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+
+        Some(Self { trace_id: trace_id.to_string(), span_id: span_id.to_string(), sampled: flags & 1 == 1 })
+    }
+
+    // Aynı trace içinde yeni bir adım
+    pub fn child(&self) -> Self {
+        Self { trace_id: self.trace_id.clone(), span_id: random_hex(8), sampled: self.sampled }
+    }
+
+    // Gelen başlık geçerliyse trace sürdürülür, değilse yeni trace başlar
+    pub fn continue_from(header: Option<&str>) -> Self {
+        header
+            .and_then(Self::parse)
+            .map(|parent| parent.child())
+            .unwrap_or_else(Self::new_root)
+    }
+
+    pub fn header(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.sampled as u8)
+    }
+}
+
+// trace_requests ile eklenen bağlam; middleware yoksa gelen başlıktan sürdürülür
+impl FromRequest for TraceContext {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let trace = req.extensions().get::<TraceContext>().cloned().unwrap_or_else(|| {
+            let header = req.headers().get(TRACEPARENT_HEADER).and_then(|value| value.to_str().ok());
+            TraceContext::continue_from(header)
+        });
+        ready(Ok(trace))
+    }
+}
+
+// Her istek için traceparent'ı sürdürür, istek span'ını açar ve tamamlandığında
+// durum ve süreyi loglar. Yanıtta traceparent döner
+pub async fn trace_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let header = req.headers().get(TRACEPARENT_HEADER).and_then(|value| value.to_str().ok());
+    let trace = TraceContext::continue_from(header);
+    let span = tracing::info_span!(
+        "http_request",
+        trace_id = %trace.trace_id,
+        span_id = %trace.span_id,
+        method = %req.method(),
+        route = %req.match_pattern().unwrap_or_default(),
+    );
+    req.extensions_mut().insert(trace.clone());
+    let started = Instant::now();
+
+    let result = next.call(req).instrument(span.clone()).await;
+
+    let _entered = span.enter();
+    let mut response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!(status = e.as_response_error().status_code().as_u16(), error = %e, "İstek başarısız");
+            return Err(e);
+        }
+    };
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    if status >= 500 {
+        tracing::error!(status, latency_ms, "İstek tamamlandı");
+    } else {
+        tracing::info!(status, latency_ms, "İstek tamamlandı");
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&trace.header()) {
+        response.headers_mut().insert(HeaderName::from_static(TRACEPARENT_HEADER), value);
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{middleware::from_fn, test::{call_service, init_service, read_body, TestRequest}, web, App, HttpResponse};
+
+    #[test]
+    fn test_parse_traceparent() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let trace = TraceContext::parse(header).unwrap();
+        assert_eq!(trace.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(trace.span_id, "00f067aa0ba902b7");
+        assert!(trace.sampled);
+        assert_eq!(trace.header(), header);
+
+        let child = TraceContext::continue_from(Some(header));
+        assert_eq!(child.trace_id, trace.trace_id);
+        assert_ne!(child.span_id, trace.span_id);
+
+        for invalid in [
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-ek",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        ] {
+            assert!(TraceContext::parse(invalid).is_none(), "{}", invalid);
+        }
+        assert!(TraceContext::parse(&TraceContext::new_root().header()).is_some());
+    }
+
+    #[actix_web::test]
+    async fn test_middleware_continues_trace() {
+        async fn handler(trace: TraceContext) -> HttpResponse {
+            HttpResponse::Ok().body(trace.trace_id)
+        }
+
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(trace_requests))
+                .route("/", web::get().to(handler))
+        ).await;
+
+        let request = TestRequest::get()
+            .uri("/")
+            .insert_header((TRACEPARENT_HEADER, "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"))
+            .to_request();
+        let response = call_service(&app, request).await;
+        let returned = TraceContext::parse(response.headers().get(TRACEPARENT_HEADER).unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(returned.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(read_body(response).await, "4bf92f3577b34da6a3ce929d0e0e4736");
+    }
+}